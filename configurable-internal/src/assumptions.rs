@@ -0,0 +1,364 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use std::collections::BTreeMap;
+
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{BinOp, Expr, Ident, Path, Token, parenthesized};
+
+/// Relation between a platform parameter and the value given in an assumption.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum AssumptionOp {
+    /// `key = value`: the platform feature must be a subtype of `value`.
+    Subtype,
+    /// `key != value`: the platform feature must not be a subtype of `value`.
+    Excludes,
+}
+
+/// A single `key = value` or `key != value` entry of an `#[assumptions(...)]` list.
+/// The value may list alternatives separated by `|`.
+#[derive(Clone)]
+pub(crate) struct Predicate {
+    pub key: Path,
+    pub op: AssumptionOp,
+    pub value: Expr,
+}
+
+impl Parse for Predicate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.call(Path::parse_mod_style)?;
+
+        let lookahead = input.lookahead1();
+        let op = if lookahead.peek(Token![!=]) {
+            input.parse::<Token![!=]>()?;
+            AssumptionOp::Excludes
+        } else if lookahead.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            AssumptionOp::Subtype
+        } else {
+            return Err(lookahead.error());
+        };
+
+        let value = input.parse::<Expr>()?;
+
+        Ok(Predicate { key, op, value })
+    }
+}
+
+impl Predicate {
+
+    pub fn key_string(&self) -> String {
+        self.key.to_token_stream().to_string().replace(" ", "")
+    }
+
+    /// The feature the dispatcher stores for this entry, as an `Arc<dyn Feature>` expression.
+    pub fn feature_tokens(&self, pn: &TokenStream) -> TokenStream {
+        let mut alternatives: Vec<TokenStream> = alternatives(&self.value).into_iter().map(|v| feature_arc(v, pn)).collect();
+        let value = if alternatives.len() > 1 {
+            quote! {
+                std::sync::Arc::new(#pn::AnyOf::new(vec![ #(#alternatives),* ])) as std::sync::Arc<dyn #pn::Feature>
+            }
+        } else {
+            alternatives.remove(0)
+        };
+
+        match self.op {
+            AssumptionOp::Subtype => value,
+            AssumptionOp::Excludes => quote! {
+                std::sync::Arc::new(#pn::Negated(#value)) as std::sync::Arc<dyn #pn::Feature>
+            },
+        }
+    }
+}
+
+// a string literal names a registered feature (or one of its aliases); any other value is a feature expression
+fn feature_arc(value: &Expr, pn: &TokenStream) -> TokenStream {
+    match value {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(name), .. }) => quote! { #pn::named_feature(#name) },
+        _ => quote! { std::sync::Arc::new(#value) as std::sync::Arc<dyn #pn::Feature> },
+    }
+}
+
+// `A | B | C` lists the alternatives of a disjunctive assumption
+pub(crate) fn alternatives(value: &Expr) -> Vec<&Expr> {
+    match value {
+        Expr::Binary(b) if matches!(b.op, BinOp::BitOr(_)) => {
+            let mut result = alternatives(&b.left);
+            result.extend(alternatives(&b.right));
+            result
+        }
+        Expr::Paren(p) => alternatives(&p.expr),
+        _ => vec![value],
+    }
+}
+
+/// Boolean assumption expression, in the style of `cfg(...)`:
+/// `all(...)`, `any(...)`, `not(...)` and `key = value` / `key != value` predicates.
+#[derive(Clone)]
+pub(crate) enum AssumptionExpr {
+    All(Vec<AssumptionExpr>),
+    Any(Vec<AssumptionExpr>),
+    Not(Box<AssumptionExpr>),
+    Pred(Box<Predicate>),
+}
+
+impl Parse for AssumptionExpr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(syn::token::Paren) {
+            let op: Ident = input.parse()?;
+            let content;
+            parenthesized!(content in input);
+            let mut args: Vec<AssumptionExpr> = Punctuated::<AssumptionExpr, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect();
+
+            return match op.to_string().as_str() {
+                "all" => Ok(AssumptionExpr::All(args)),
+                "any" => Ok(AssumptionExpr::Any(args)),
+                "not" if args.len() == 1 => Ok(AssumptionExpr::Not(Box::new(args.remove(0)))),
+                "not" => Err(syn::Error::new_spanned(op, "`not(...)` takes exactly one assumption")),
+                _ => Err(syn::Error::new_spanned(op, "expected `all`, `any`, `not` or `key = value`")),
+            };
+        }
+
+        Ok(AssumptionExpr::Pred(Box::new(input.parse()?)))
+    }
+}
+
+/// Contents of `#[assumptions(...)]`: a comma-separated list read as `all(...)`, in which a
+/// top-level `priority = n` entry gives the priority of the variant instead of a constraint.
+pub(crate) struct AssumptionList {
+    pub expr: AssumptionExpr,
+    pub priority: Option<Expr>,
+}
+
+impl Parse for AssumptionList {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Vec::new();
+        let mut priority = None;
+        for arg in Punctuated::<AssumptionExpr, Token![,]>::parse_terminated(input)? {
+            match arg {
+                AssumptionExpr::Pred(p) if p.key.is_ident("priority") => {
+                    if p.op != AssumptionOp::Subtype || priority.is_some() {
+                        return Err(syn::Error::new_spanned(&p.key, "expected a single `priority = n`"));
+                    }
+                    priority = Some(p.value);
+                }
+                arg => args.push(arg),
+            }
+        }
+        Ok(AssumptionList { expr: AssumptionExpr::All(args), priority })
+    }
+}
+
+impl AssumptionExpr {
+
+    // pushes negations down to the predicates (De Morgan)
+    fn negate(self) -> AssumptionExpr {
+        match self {
+            AssumptionExpr::All(args) => AssumptionExpr::Any(args.into_iter().map(|a| a.negate()).collect()),
+            AssumptionExpr::Any(args) => AssumptionExpr::All(args.into_iter().map(|a| a.negate()).collect()),
+            AssumptionExpr::Not(arg) => *arg,
+            AssumptionExpr::Pred(mut p) => {
+                p.op = match p.op {
+                    AssumptionOp::Subtype => AssumptionOp::Excludes,
+                    AssumptionOp::Excludes => AssumptionOp::Subtype,
+                };
+                AssumptionExpr::Pred(p)
+            }
+        }
+    }
+
+    /// Normalizes the expression into disjunctive normal form: a list of clauses,
+    /// each a conjunction of predicates. `any()` yields no clause, `all()` one empty clause.
+    pub fn clauses(self) -> Vec<Vec<Predicate>> {
+        match self {
+            AssumptionExpr::Pred(p) => vec![vec![*p]],
+            AssumptionExpr::Not(arg) => arg.negate().clauses(),
+            AssumptionExpr::Any(args) => args.into_iter().flat_map(|a| a.clauses()).collect(),
+            AssumptionExpr::All(args) => {
+                let mut result = vec![vec![]];
+                for a in args {
+                    let arg_clauses = a.clauses();
+                    let mut product = Vec::new();
+                    for left in &result {
+                        for right in &arg_clauses {
+                            let mut c: Vec<Predicate> = left.clone();
+                            c.extend(right.iter().cloned());
+                            product.push(c);
+                        }
+                    }
+                    result = product;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Expression building the `FeatureSet` of a clause.
+/// Predicates on the same parameter are combined with `AllOf`.
+pub(crate) fn clause_tokens(clause: &[Predicate], pn: &TokenStream) -> TokenStream {
+    let mut by_key: BTreeMap<String, Vec<TokenStream>> = BTreeMap::new();
+    for predicate in clause {
+        by_key.entry(predicate.key_string()).or_default().push(predicate.feature_tokens(pn));
+    }
+
+    let pairs = by_key.into_iter().map(|(key, mut features)| {
+        let feature = if features.len() == 1 {
+            features.remove(0)
+        } else {
+            quote! {
+                std::sync::Arc::new(#pn::AllOf::new(vec![ #(#features),* ])) as std::sync::Arc<dyn #pn::Feature>
+            }
+        };
+        quote! { (#key.to_string(), #feature) }
+    });
+
+    quote! {
+        <#pn::FeatureSet as std::iter::FromIterator<_>>::from_iter([
+            #(#pairs),*
+        ])
+    }
+}
+
+/// Constraints of a single-clause assumption, by key (`None` for `any(...)` expressions
+/// with several clauses, or unparsable ones).
+pub(crate) fn clause_signature(tokens: &TokenStream) -> Option<BTreeMap<String, Vec<String>>> {
+    let mut clauses = syn::parse2::<AssumptionList>(tokens.clone()).ok()?.expr.clauses();
+    if clauses.len() != 1 { return None; }
+
+    let mut signature: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for p in clauses.remove(0) {
+        let op = if p.op == AssumptionOp::Subtype { "=" } else { "!=" };
+        signature.entry(p.key_string()).or_default().push(format!("{op} {}", p.value.to_token_stream()));
+    }
+    for constraints in signature.values_mut() { constraints.sort(); }
+    Some(signature)
+}
+
+// the priority of an assumption as written, empty if it has none
+fn priority_string(tokens: &TokenStream) -> String {
+    syn::parse2::<AssumptionList>(tokens.clone()).ok()
+        .and_then(|list| list.priority)
+        .map(|p| p.to_token_stream().to_string())
+        .unwrap_or_default()
+}
+
+/// Statements raising a warning (through a deprecated item) for each pair of variants that
+/// are known at compile time to be incomparable while a platform may satisfy both: their
+/// keys are not included one in the other, they constrain their common keys identically and
+/// they are written with the same priority. No warning is raised when a variant assumes
+/// exactly the constraints of both, as it is selected on the platforms satisfying both.
+pub(crate) fn ambiguity_warnings(name: &str, assumptions: &[Option<TokenStream>]) -> TokenStream {
+    let signatures: Vec<Option<BTreeMap<String, Vec<String>>>> = assumptions.iter()
+        .map(|a| a.as_ref().filter(|t| !t.is_empty()).and_then(clause_signature))
+        .collect();
+    let priorities: Vec<String> = assumptions.iter().map(|a| a.as_ref().map(priority_string).unwrap_or_default()).collect();
+
+    let mut warnings = Vec::new();
+    for (i, si) in signatures.iter().enumerate() {
+        for (j, sj) in signatures.iter().enumerate().skip(i + 1) {
+            let (Some(si), Some(sj)) = (si, sj) else { continue };
+            if priorities[i] != priorities[j] { continue; }
+            let included = si.keys().all(|k| sj.contains_key(k)) || sj.keys().all(|k| si.contains_key(k));
+            let agree = si.iter().all(|(k, v)| sj.get(k).is_none_or(|w| w == v));
+            if included || !agree { continue; }
+
+            // the meet of the pair; a variant assuming more leaves platforms satisfying the pair only
+            let mut meet = si.clone();
+            meet.extend(sj.clone());
+            if signatures.iter().flatten().any(|s| *s == meet) { continue; }
+
+            let keys = |s: &BTreeMap<String, Vec<String>>| s.keys().cloned().collect::<Vec<String>>().join(", ");
+            let note = format!(
+                "variants #{i} ({}) and #{j} ({}) of `{name}` are incomparable: on a platform satisfying both, the choice follows the ambiguity policy",
+                keys(si), keys(sj)
+            );
+            let span = assumptions[j].as_ref().and_then(|t| t.clone().into_iter().next()).map(|t| t.span()).unwrap_or_else(Span::call_site);
+            warnings.push(quote::quote_spanned! { span =>
+                {
+                    #[deprecated(note = #note)]
+                    #[allow(non_camel_case_types)]
+                    struct ambiguous_kernel_variants;
+                    let _ = ambiguous_kernel_variants;
+                }
+            });
+        }
+    }
+
+    quote! { #(#warnings)* }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // clauses of an assumption list, each as sorted `key op value` strings
+    fn dnf(tokens: TokenStream) -> Vec<Vec<String>> {
+        syn::parse2::<AssumptionList>(tokens).unwrap().expr.clauses().into_iter()
+            .map(|clause| {
+                let mut predicates: Vec<String> = clause.iter().map(|p| {
+                    let op = if p.op == AssumptionOp::Subtype { "=" } else { "!=" };
+                    format!("{} {op} {}", p.key_string(), p.value.to_token_stream())
+                }).collect();
+                predicates.sort();
+                predicates
+            })
+            .collect()
+    }
+
+    #[test]
+    fn list_is_a_conjunction() {
+        assert_eq!(dnf(quote! { cpu_simd = AVX2, acc_model != NVIDIA_GPU }), vec![vec!["acc_model != NVIDIA_GPU", "cpu_simd = AVX2"]]);
+        assert_eq!(dnf(quote! {}), vec![Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn conjunction_distributes_over_disjunction() {
+        assert_eq!(
+            dnf(quote! { all(cpu_simd = AVX2, any(acc_model = NVIDIA_GPU, cores = AtLeast { val: 32 })) }),
+            vec![vec!["acc_model = NVIDIA_GPU", "cpu_simd = AVX2"], vec!["cores = AtLeast { val : 32 }", "cpu_simd = AVX2"]]
+        );
+        assert_eq!(dnf(quote! { any(a = A, b = B), any(c = C, d = D) }).len(), 4);
+        assert!(dnf(quote! { any() }).is_empty());
+    }
+
+    #[test]
+    fn negation_follows_de_morgan() {
+        assert_eq!(dnf(quote! { not(all(a = A, b != B)) }), vec![vec!["a != A"], vec!["b = B"]]);
+        assert_eq!(dnf(quote! { not(any(a = A, b = B)) }), vec![vec!["a != A", "b != B"]]);
+        assert_eq!(dnf(quote! { not(not(a = A)) }), vec![vec!["a = A"]]);
+        assert!(syn::parse2::<AssumptionList>(quote! { not(a = A, b = B) }).is_err());
+    }
+
+    #[test]
+    fn priority_is_not_a_constraint() {
+        let list = syn::parse2::<AssumptionList>(quote! { a = A, priority = 10 }).unwrap();
+        assert_eq!(list.priority.map(|p| p.to_token_stream().to_string()), Some("10".to_string()));
+        assert_eq!(dnf(quote! { a = A, priority = 10 }), vec![vec!["a = A"]]);
+        assert!(syn::parse2::<AssumptionList>(quote! { priority = 1, priority = 2 }).is_err());
+    }
+
+    #[test]
+    fn alternatives_split_on_bit_or() {
+        let p = syn::parse2::<Predicate>(quote! { acc_model = NVIDIA_GPU | (AMD_GPU | Intel_GPU) }).unwrap();
+        assert_eq!(alternatives(&p.value).len(), 3);
+    }
+
+    #[test]
+    fn incomparable_variants_are_warned() {
+        let avx = Some(quote! { cpu_simd = AVX512F });
+        let gpu = Some(quote! { acc_model = NVIDIA_GPU });
+        let both = Some(quote! { cpu_simd = AVX512F, acc_model = NVIDIA_GPU });
+        assert!(!ambiguity_warnings("f", &[None, avx.clone(), gpu.clone()]).is_empty());
+        assert!(ambiguity_warnings("f", &[None, avx.clone(), both.clone()]).is_empty());
+
+        // a variant assuming both settles the pair, one assuming more does not
+        assert!(ambiguity_warnings("f", &[None, avx.clone(), gpu.clone(), both]).is_empty());
+        let more = Some(quote! { cpu_simd = AVX512F, acc_model = NVIDIA_GPU, cpu_cores = AtLeast(8) });
+        assert_eq!(ambiguity_warnings("f", &[None, avx.clone(), gpu, more]).to_string().matches("deprecated").count(), 1);
+        assert!(ambiguity_warnings("f", &[None, avx, Some(quote! { acc_model = NVIDIA_GPU, priority = 1 })]).is_empty());
+    }
+}
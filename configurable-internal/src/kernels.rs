@@ -0,0 +1,219 @@
+use quote::ToTokens;
+use syn::{Expr, ImplItem, Item, ItemMod, Lit, UnOp};
+
+use crate::assumptions::{AssumptionList, AssumptionOp, Predicate, alternatives};
+use crate::{expand_includes, extract_assumptions, has_assumptions};
+
+/// A value of an assumption written literally, which a build script can build without the
+/// types of the crate.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiteralFeature {
+    /// a registered feature, by name (an identifier or a string, aliases included)
+    Named(String),
+    Exactly(i32),
+    AtLeast(i32),
+    AtMost(i32),
+    AnyOf(Vec<LiteralFeature>),
+}
+
+/// A `key = value` or `key != value` entry of an assumption written literally.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiteralPredicate {
+    pub key: String,
+    pub excluded: bool,
+    pub value: LiteralFeature,
+}
+
+/// An assumption written literally, in disjunctive normal form.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiteralAssumption {
+    pub clauses: Vec<Vec<LiteralPredicate>>,
+    pub priority: i32,
+}
+
+/// The variants of a kernel function found in a source file (see `__kernel_functions`).
+#[derive(Clone, Debug)]
+pub struct KernelFunction {
+    /// `module::function`, or `module::Type::method`, as the dispatcher looks it up in the pin
+    pub key: String,
+    /// the assumptions of the variants in declaration order, `None` if one is not written
+    /// literally (its dispatcher then resolves at run time)
+    pub variants: Option<Vec<LiteralAssumption>>,
+}
+
+// integer literal, possibly negated
+fn int_literal(expr: &Expr) -> Option<i32> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Int(i), .. }) => i.base10_parse().ok(),
+        Expr::Unary(u) if matches!(u.op, UnOp::Neg(_)) => int_literal(&u.expr).map(|i| -i),
+        Expr::Paren(p) => int_literal(&p.expr),
+        _ => None,
+    }
+}
+
+// a single alternative of an assumption value
+fn literal_alternative(expr: &Expr) -> Option<LiteralFeature> {
+    if let Some(i) = int_literal(expr) {
+        return Some(LiteralFeature::Exactly(i));
+    }
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(name), .. }) => Some(LiteralFeature::Named(name.value())),
+        Expr::Path(p) => Some(LiteralFeature::Named(p.path.segments.last()?.ident.to_string())),
+        Expr::Struct(s) if s.fields.len() == 1 => {
+            let field = s.fields.first()?;
+            matches!(&field.member, syn::Member::Named(m) if m == "val").then_some(())?;
+            let val = int_literal(&field.expr)?;
+            match s.path.segments.last()?.ident.to_string().as_str() {
+                "AtLeast" => Some(LiteralFeature::AtLeast(val)),
+                "AtMost" => Some(LiteralFeature::AtMost(val)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn literal_predicate(predicate: &Predicate) -> Option<LiteralPredicate> {
+    let mut values = alternatives(&predicate.value).into_iter().map(literal_alternative).collect::<Option<Vec<_>>>()?;
+    Some(LiteralPredicate {
+        key: predicate.key_string(),
+        excluded: predicate.op == AssumptionOp::Excludes,
+        value: if values.len() == 1 { values.remove(0) } else { LiteralFeature::AnyOf(values) },
+    })
+}
+
+/// The assumption of a variant, if it is written literally (no arguments: the fallback).
+pub(crate) fn literal_assumption(tokens: &Option<proc_macro2::TokenStream>) -> Option<LiteralAssumption> {
+    let Some(tokens) = tokens.as_ref().filter(|t| !t.is_empty()) else { return Some(LiteralAssumption { clauses: vec![vec![]], priority: 0 }) };
+    let list = syn::parse2::<AssumptionList>(tokens.clone()).ok()?;
+    let priority = match &list.priority {
+        Some(p) => int_literal(p)?,
+        None => 0,
+    };
+    let clauses = list.expr.clauses().iter()
+        .map(|c| c.iter().map(literal_predicate).collect::<Option<Vec<_>>>())
+        .collect::<Option<Vec<_>>>()?;
+    Some(LiteralAssumption { clauses, priority })
+}
+
+fn kernel_function(key: String, assumptions: &[Option<proc_macro2::TokenStream>]) -> KernelFunction {
+    KernelFunction { key, variants: assumptions.iter().map(literal_assumption).collect() }
+}
+
+// the kernel functions of a kernel module, grouped as the macro groups them
+fn module_functions(mut item_mod: ItemMod, macro_name: &str, attr_name: &str) -> syn::Result<Vec<KernelFunction>> {
+    expand_includes(&mut item_mod, macro_name)?;
+    let module = item_mod.ident.to_string();
+    let mut groups: Vec<(String, Vec<Option<proc_macro2::TokenStream>>)> = Vec::new();
+    let mut push = |name: String, assumptions| match groups.iter_mut().find(|(n, _)| *n == name) {
+        Some((_, group)) => group.push(assumptions),
+        None => groups.push((name, vec![assumptions])),
+    };
+
+    for item in item_mod.content.map(|(_, items)| items).unwrap_or_default() {
+        match item {
+            Item::Fn(mut func) if has_assumptions(&func.attrs, attr_name) => {
+                push(format!("{module}::{}", func.sig.ident), extract_assumptions(&mut func.attrs, attr_name));
+            }
+            Item::Impl(impl_block) => {
+                let self_ty = impl_block.self_ty.to_token_stream().to_string().replace(' ', "");
+                for item in impl_block.items {
+                    if let ImplItem::Fn(mut method) = item && has_assumptions(&method.attrs, attr_name) {
+                        push(format!("{module}::{self_ty}::{}", method.sig.ident), extract_assumptions(&mut method.attrs, attr_name));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(groups.into_iter().map(|(key, assumptions)| kernel_function(key, &assumptions)).collect())
+}
+
+fn collect_functions(items: Vec<Item>, macro_name: &str, attr_name: &str, functions: &mut Vec<KernelFunction>) -> syn::Result<()> {
+    for item in items {
+        let Item::Mod(item_mod) = item else { continue };
+        if item_mod.attrs.iter().any(|a| a.path().segments.last().is_some_and(|s| s.ident == macro_name)) {
+            functions.extend(module_functions(item_mod, macro_name, attr_name)?);
+        } else if let Some((_, items)) = item_mod.content {
+            collect_functions(items, macro_name, attr_name, functions)?;
+        }
+    }
+    Ok(())
+}
+
+/// The kernel functions of the modules marked with `#[<macro_name>]` in a source file, with
+/// the literal assumptions of their variants, for a build script to resolve them (see
+/// `export_platform_pin`). Includes are read relative to `CARGO_MANIFEST_DIR`, as the macro
+/// reads them.
+#[doc(hidden)]
+pub fn __kernel_functions(source: &str, macro_name: &str, attr_name: &str) -> syn::Result<Vec<KernelFunction>> {
+    let file: syn::File = syn::parse_str(source)?;
+    let mut functions = Vec::new();
+    collect_functions(file.items, macro_name, attr_name, &mut functions)?;
+    Ok(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    fn predicate(key: &str, excluded: bool, value: LiteralFeature) -> LiteralPredicate {
+        LiteralPredicate { key: key.to_string(), excluded, value }
+    }
+
+    #[test]
+    fn reads_literal_assumptions() {
+        let read = |tokens| literal_assumption(&Some(tokens));
+        assert_eq!(literal_assumption(&None), Some(LiteralAssumption { clauses: vec![vec![]], priority: 0 }));
+
+        let assumption = read(quote! { acc = NVIDIA_GPU | "AMD_GPU", cores != AtMost{val: 4}, priority = -2 }).unwrap();
+        assert_eq!(assumption.priority, -2);
+        assert_eq!(assumption.clauses, vec![vec![
+            predicate("acc", false, LiteralFeature::AnyOf(vec![LiteralFeature::Named("NVIDIA_GPU".to_string()), LiteralFeature::Named("AMD_GPU".to_string())])),
+            predicate("cores", true, LiteralFeature::AtMost(4)),
+        ]]);
+        assert_eq!(read(quote! { any(cores = 8, not(simd = AVX)) }).unwrap().clauses, vec![
+            vec![predicate("cores", false, LiteralFeature::Exactly(8))],
+            vec![predicate("simd", true, LiteralFeature::Named("AVX".to_string()))],
+        ]);
+
+        // values and priorities computed at run time
+        assert_eq!(read(quote! { cores = AtLeast{val: N} }), None);
+        assert_eq!(read(quote! { acc = gpu(), priority = 1 }), None);
+        assert_eq!(read(quote! { acc = GPU, priority = P }), None);
+    }
+
+    #[test]
+    fn finds_the_kernel_functions_of_a_source() {
+        let source = r#"
+            mod outer {
+                #[configurable]
+                mod kernels {
+                    #[assumptions]
+                    pub fn saxpy() {}
+                    #[assumptions(acc = GPU)]
+                    pub fn saxpy() {}
+                    pub fn helper() {}
+                    #[assumptions(acc = gpu())]
+                    pub fn scale() {}
+
+                    impl Solver<T> {
+                        #[assumptions(acc = GPU)]
+                        fn solve(&self) {}
+                    }
+                }
+            }
+            mod plain {
+                #[assumptions(acc = GPU)]
+                pub fn ignored() {}
+            }
+        "#;
+        let functions = __kernel_functions(source, "configurable", "assumptions").unwrap();
+        let keys: Vec<&str> = functions.iter().map(|f| f.key.as_str()).collect();
+        assert_eq!(keys, vec!["kernels::saxpy", "kernels::scale", "kernels::Solver<T>::solve"]);
+        assert_eq!(functions[0].variants.as_ref().map(Vec::len), Some(2));
+        assert!(functions[1].variants.is_none());
+    }
+}
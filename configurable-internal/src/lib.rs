@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote, ToTokens};
+use quote::{ToTokens, format_ident, quote};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -8,19 +8,54 @@ use syn::{
     Signature, Visibility, parse2
 };
 
+mod assumptions;
+mod kernels;
+mod options;
+mod pin;
+
+use assumptions::{AssumptionList, ambiguity_warnings, clause_tokens};
+use options::ModuleOptions;
+use pin::PlatformPin;
+
+pub use kernels::{KernelFunction, LiteralAssumption, LiteralFeature, LiteralPredicate, __kernel_functions};
+
 /// The core logic function.
 /// 
 /// This is exposed as a library function so that a proc-macro crate can call it
 /// with specific configuration (e.g. "assumptions" vs "kernelversion").
 pub fn __internal_configurable(item: TokenStream, macro_name: &str, attr_name: &str, package_name: &str) -> TokenStream {    
+    __internal_configurable_with(TokenStream::new(), item, macro_name, attr_name, package_name)
+}
+
+/// Like `__internal_configurable`, with the arguments of the module attribute
+/// (`resolver = expr`).
+///
+/// If `CONFIGURABLE_PLATFORM_PIN` names a platform pin (see `export_platform_pin`), dispatchers
+/// whose variant the pin holds call it directly, and the other variants are dropped.
+pub fn __internal_configurable_with(attr: TokenStream, item: TokenStream, macro_name: &str, attr_name: &str, package_name: &str) -> TokenStream {
+    let mut options = match parse2::<ModuleOptions>(attr) {
+        Ok(options) => options,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    match PlatformPin::from_env() {
+        Some(Ok(pin)) => options.pin = Some(pin),
+        Some(Err(e)) => return syn::Error::new(proc_macro2::Span::call_site(), e).into_compile_error(),
+        None => {}
+    }
+
     let mut item_mod = parse2::<ItemMod>(item).expect("Must be applied to a module");
+    options.module = item_mod.ident.to_string();
 
     if let Err(e) = expand_includes(&mut item_mod, macro_name) {
-        return e.into_compile_error().into();
+        return e.into_compile_error();
     }
 
     if let Some((_, ref mut items)) = item_mod.content {
         let mut new_items = Vec::new();
+        if let Some(pin) = &options.pin {
+            new_items.push(Item::Verbatim(pin.tracking_tokens()));
+        }
         
         let mut fn_groups: HashMap<String, Vec<FunctionVariant>> = HashMap::new();
         
@@ -39,7 +74,7 @@ pub fn __internal_configurable(item: TokenStream, macro_name: &str, attr_name: &
                     }
                 }
                 Item::Impl(impl_block) => {
-                    let processed_blocks = process_impl_block(impl_block, attr_name, package_name);
+                    let processed_blocks = process_impl_block(impl_block, attr_name, package_name, &options);
                     new_items.extend(processed_blocks);
                 }
                 _ => new_items.push(item),
@@ -47,14 +82,14 @@ pub fn __internal_configurable(item: TokenStream, macro_name: &str, attr_name: &
         }
 
         for (name, variants) in fn_groups {
-            let dispatcher = generate_dispatch(&name, variants, package_name);
+            let dispatcher = generate_dispatch(&name, variants, package_name, &options);
             new_items.extend(dispatcher);
         }
         
         *items = new_items;
     }
 
-    TokenStream::from(quote! { #item_mod })
+    quote! { #item_mod }
 }
 
 struct FunctionVariant {
@@ -62,7 +97,7 @@ struct FunctionVariant {
     assumptions: Option<proc_macro2::TokenStream>,
 }
 
-fn process_impl_block(mut impl_block: ItemImpl, attr_name: &str, package_name: &str) -> Vec<Item> {
+fn process_impl_block(mut impl_block: ItemImpl, attr_name: &str, package_name: &str, options: &ModuleOptions) -> Vec<Item> {
     let mut method_groups: HashMap<String, Vec<ImplItem>> = HashMap::new();
     let mut methods_assumptions: HashMap<String, Vec<Option<proc_macro2::TokenStream>>> = HashMap::new();
     let mut other_items = Vec::new();
@@ -88,10 +123,13 @@ fn process_impl_block(mut impl_block: ItemImpl, attr_name: &str, package_name: &
     for (name, mut methods) in method_groups {
         let assumptions_list = methods_assumptions
             .remove(&name)
-            .expect(&format!("Mismatched assumptions for methoded: {}", name));
+            .unwrap_or_else(|| panic!("Mismatched assumptions for methoded: {}", name));
         
         let master_sig = if let ImplItem::Fn(m) = &methods[0] { m.sig.clone() } else { unreachable!() };
         let vis = if let ImplItem::Fn(m) = &methods[0] { m.vis.clone() } else { unreachable!() };
+        let fallback_idx = assumptions_list.iter().position(is_empty_assumption).unwrap_or(0);
+        let qualified_name = format!("{}::{name}", impl_block.self_ty.to_token_stream().to_string().replace(' ', ""));
+        let pinned = options.pinned_variant(&qualified_name, &master_sig, assumptions_list.len(), fallback_idx);
 
         let mut variant_idents = Vec::new();
         for (i, method_item) in methods.iter_mut().enumerate() {
@@ -108,10 +146,12 @@ fn process_impl_block(mut impl_block: ItemImpl, attr_name: &str, package_name: &
                     m.attrs.push(syn::parse_quote!(#[doc(hidden)]));
                 }
             }
-            variants_to_add.push(method_item.clone());
+            if pinned.is_none_or(|k| k == i) {
+                variants_to_add.push(method_item.clone());
+            }
         }
 
-        let dispatcher = generate_impl_dispatcher(&name, &master_sig, &vis, &variant_idents, &assumptions_list, package_name);
+        let dispatcher = generate_impl_dispatcher(&qualified_name, &master_sig, &vis, &variant_idents, &assumptions_list, package_name, options);
         other_items.push(dispatcher);
     }
 
@@ -174,7 +214,7 @@ fn read_included_file(path: &str) -> syn::Result<String> {
     })
 }
 
-fn generate_dispatch(original_name: &str, variants: Vec<FunctionVariant>, package_name: &str) -> Vec<Item> {
+fn generate_dispatch(original_name: &str, variants: Vec<FunctionVariant>, package_name: &str, options: &ModuleOptions) -> Vec<Item> {
     let mut items = Vec::new();
     let mut variant_names = Vec::new();
     let mut assumption_tokens = Vec::new();
@@ -182,21 +222,28 @@ fn generate_dispatch(original_name: &str, variants: Vec<FunctionVariant>, packag
     let master_sig = if let Item::Fn(f) = &variants[0].item { f.sig.clone() } else { panic!("Not a function") };
     let master_vis = if let Item::Fn(f) = &variants[0].item { f.vis.clone() } else { panic!("Not a function") };
 
+    let fallback_idx = variants.iter()
+        .position(|v| is_empty_assumption(&v.assumptions))
+        .unwrap_or(0); 
+    let pinned = options.pinned_variant(original_name, &master_sig, variants.len(), fallback_idx);
+
     for (i, variant) in variants.into_iter().enumerate() {
         let mut func = if let Item::Fn(f) = variant.item { f } else { panic!() };
         let new_ident = format_ident!("{}_variant_{}", original_name, i);
         func.sig.ident = new_ident.clone();
         
-        items.push(Item::Fn(func));
+        if pinned.is_none_or(|k| k == i) {
+            items.push(Item::Fn(func));
+        }
         variant_names.push(new_ident);
         assumption_tokens.push(variant.assumptions);
     }
 
-    let fallback_idx = assumption_tokens.iter()
-        .position(|t| is_empty_assumption(t))
-        .unwrap_or(0); 
+    let pn = <TokenStream as std::str::FromStr>::from_str(package_name).expect("invalid package name");
 
-    let platforms_vec = build_platforms_vec(&assumption_tokens);
+    let platforms_vec = build_platforms_vec(&assumption_tokens, &pn);
+    let warnings = if options.warns_ambiguity() { ambiguity_warnings(original_name, &assumption_tokens) } else { quote! {} };
+    let resolve = options.resolve_tokens(&pn);
     let args = args_from_sig(&master_sig);
     
     let await_call = if master_sig.asyncness.is_some() { quote!{.await} } else { quote!{} };
@@ -225,25 +272,51 @@ fn generate_dispatch(original_name: &str, variants: Vec<FunctionVariant>, packag
     let unsafety = &master_sig.unsafety;
     let abi = &master_sig.abi;
 
-    let pn = <TokenStream as std::str::FromStr>::from_str(package_name).expect("invalid package name");
-
-    let dispatcher = quote! {
-        #master_vis #constness #asyncness #unsafety #abi fn #ident #generics (#inputs) #output #where_clause {
+    let body = match pinned {
+        Some(k) => {
+            let pinned_ident = &variant_names[k];
+            quote! {
+                static PINNED: std::sync::Once = std::sync::Once::new();
+                PINNED.call_once(|| #pn::check_pinned_resolver(concat!(module_path!(), "::", #original_name)));
+                #pinned_ident(#args) #await_call
+            }
+        }
+        None if options.chains(&master_sig) => {
+            let retry_args = retry_args_from_sig(&master_sig);
+            let calls: Vec<(TokenStream, TokenStream)> = variant_names.iter()
+                .map(|v| (quote! { #v(#retry_args) #await_call }, quote! { #v(#args) #await_call }))
+                .collect();
+            let checks = clone_assertions(&master_sig);
+            fallback_chain_body(original_name, &pn, &platforms_vec, &options.rank_tokens(&pn), &calls, fallback_idx, &quote! { #warnings #checks })
+        }
+        None => quote! {
             use std::sync::Arc;
-            use std::collections::HashMap;
             use lazy_static::lazy_static;
-            use #pn::{resolve,Feature};
+            use #pn::Feature;
+
+            #warnings
+
+            fn select_variant() -> i32 {
+                let variants = vec![#platforms_vec];
+                #resolve
+            }
 
             lazy_static! {
-                static ref SELECTED_VARIANT: i32 = {
-                    let variants = vec![#platforms_vec];
-                    resolve(variants)
-                };
+                static ref SELECTED_VARIANT: i32 = select_variant();
             }
 
-            match *SELECTED_VARIANT {
+            // scoped platforms (see `with_platform`) bypass the cached choice
+            let selected = if #pn::is_platform_overridden() { select_variant() } else { *SELECTED_VARIANT };
+
+            match selected {
                 #(#match_arms),*
             }
+        },
+    };
+
+    let dispatcher = quote! {
+        #master_vis #constness #asyncness #unsafety #abi fn #ident #generics (#inputs) #output #where_clause {
+            #body
         }
     };
 
@@ -251,20 +324,27 @@ fn generate_dispatch(original_name: &str, variants: Vec<FunctionVariant>, packag
     items
 }
 
+// `name` is qualified by the type of the impl block (`Type::method`)
 fn generate_impl_dispatcher(
     name: &str, 
     sig: &Signature, 
     vis: &Visibility, 
     variants: &[proc_macro2::Ident], 
     assumptions: &[Option<proc_macro2::TokenStream>],
-    package_name: &str
+    package_name: &str,
+    options: &ModuleOptions
 ) -> ImplItem {
-    let platforms_vec = build_platforms_vec(assumptions);
+    let pn = <TokenStream as std::str::FromStr>::from_str(package_name).expect("invalid package name");
+
+    let platforms_vec = build_platforms_vec(assumptions, &pn);
+    let warnings = if options.warns_ambiguity() { ambiguity_warnings(name, assumptions) } else { quote! {} };
+    let resolve = options.resolve_tokens(&pn);
     let args = args_from_sig(sig);
     
     let fallback_idx = assumptions.iter()
-        .position(|t| is_empty_assumption(t))
+        .position(is_empty_assumption)
         .unwrap_or(0);
+    let pinned = options.pinned_variant(name, sig, assumptions.len(), fallback_idx);
 
     let await_call = if sig.asyncness.is_some() { quote!{.await} } else { quote!{} };
 
@@ -282,7 +362,7 @@ fn generate_impl_dispatcher(
     let fallback = &variants[fallback_idx];
     method_match_arms.push(quote! { _ => #call_prefix #fallback(#args) #await_call });
 
-    let ident = format_ident!("{}", name);
+    let ident = format_ident!("{}", name.rsplit("::").next().unwrap_or(name));
     let generics = &sig.generics;
     let inputs = &sig.inputs;
     let output = &sig.output;
@@ -292,31 +372,122 @@ fn generate_impl_dispatcher(
     let asyncness = &sig.asyncness;
     let unsafety = &sig.unsafety;
     let abi = &sig.abi;
-    let pn = <TokenStream as std::str::FromStr>::from_str(package_name).expect("invalid package name");
 
-    let item = quote! {
-        #vis #constness #asyncness #unsafety #abi fn #ident #generics (#inputs) #output #where_clause {
+    let body = match pinned {
+        Some(k) => {
+            let pinned_ident = &variants[k];
+            quote! {
+                static PINNED: std::sync::Once = std::sync::Once::new();
+                PINNED.call_once(|| #pn::check_pinned_resolver(concat!(module_path!(), "::", #name)));
+                #call_prefix #pinned_ident(#args) #await_call
+            }
+        }
+        None if options.chains(sig) => {
+            let retry_args = retry_args_from_sig(sig);
+            let calls: Vec<(TokenStream, TokenStream)> = variants.iter()
+                .map(|v| (quote! { #call_prefix #v(#retry_args) #await_call }, quote! { #call_prefix #v(#args) #await_call }))
+                .collect();
+            let checks = clone_assertions(sig);
+            fallback_chain_body(name, &pn, &platforms_vec, &options.rank_tokens(&pn), &calls, fallback_idx, &quote! { #warnings #checks })
+        }
+        None => quote! {
             use std::sync::Arc;
-            use std::collections::HashMap;
             use lazy_static::lazy_static;
-            use #pn::{resolve,Feature};
+            use #pn::Feature;
+
+            #warnings
+
+            fn select_variant() -> i32 {
+                let variants = vec![#platforms_vec];
+                #resolve
+            }
 
             lazy_static! {
-                static ref SELECTED_VARIANT: i32 = {
-                    let variants = vec![#platforms_vec];
-                    resolve(variants)
-                };
+                static ref SELECTED_VARIANT: i32 = select_variant();
             }
 
-            match *SELECTED_VARIANT {
+            // scoped platforms (see `with_platform`) bypass the cached choice
+            let selected = if #pn::is_platform_overridden() { select_variant() } else { *SELECTED_VARIANT };
+
+            match selected {
                 #(#method_match_arms),*
             }
+        },
+    };
+
+    let item = quote! {
+        #vis #constness #asyncness #unsafety #abi fn #ident #generics (#inputs) #output #where_clause {
+            #body
         }
     };
     
     syn::parse2(item).expect("Failed to parse impl dispatcher")
 }
 
+// body of a dispatcher in `fallback_on_error` mode: the ranked variants are called in turn
+// until one succeeds or the fallback is reached, later calls skipping those that failed; each
+// variant has a call with copies of the arguments, for the earlier candidates, and one taking
+// them, for the last. `checks` are statements checking the declaration at compile time
+// (ambiguity warnings, `Clone` arguments).
+fn fallback_chain_body(
+    name: &str,
+    pn: &TokenStream,
+    platforms_vec: &TokenStream,
+    rank: &TokenStream,
+    calls: &[(TokenStream, TokenStream)],
+    fallback_idx: usize,
+    checks: &TokenStream
+) -> TokenStream {
+    let idx_lits = (0..calls.len()).map(|idx| syn::LitInt::new(&idx.to_string(), proc_macro2::Span::call_site()));
+    let retry_arms = idx_lits.clone().zip(calls).map(|(idx_lit, (retry, _))| quote! { #idx_lit => #retry });
+    let last_arms = idx_lits.zip(calls).map(|(idx_lit, (_, call))| quote! { #idx_lit => #call });
+
+    quote! {
+        use std::sync::Arc;
+        use lazy_static::lazy_static;
+        use #pn::Feature;
+
+        #checks
+
+        fn rank_variants() -> Vec<usize> {
+            let variants = vec![#platforms_vec];
+            #rank
+        }
+
+        lazy_static! {
+            static ref FALLBACK_CHAIN: #pn::FallbackChain = #pn::FallbackChain::new(rank_variants(), #fallback_idx);
+        }
+
+        // scoped platforms (see `with_platform`) neither use nor update the shared chain
+        let scoped;
+        let chain: &#pn::FallbackChain = if #pn::is_platform_overridden() {
+            scoped = #pn::FallbackChain::new(rank_variants(), #fallback_idx);
+            &scoped
+        } else {
+            &*FALLBACK_CHAIN
+        };
+
+        let candidates = chain.candidates();
+        let (&last, earlier) = candidates.split_last().expect("the fallback is always a candidate");
+        for (k, variant) in earlier.iter().copied().enumerate() {
+            let result = match variant {
+                #(#retry_arms,)*
+                _ => unreachable!(),
+            };
+            // a type named `Result` that is not `std::result::Result` fails to compile here
+            if !::core::result::Result::is_err(&result) {
+                return result;
+            }
+            chain.mark_failed(variant);
+            #pn::report_variant_failure(#name, variant, candidates[k + 1]);
+        }
+        match last {
+            #(#last_arms,)*
+            _ => unreachable!(),
+        }
+    }
+}
+
 fn is_empty_assumption(tokens: &Option<proc_macro2::TokenStream>) -> bool {
     match tokens {
         None => true,
@@ -338,55 +509,77 @@ fn extract_assumptions(attrs: &mut Vec<Attribute>, attr_name: &str) -> Option<pr
     Some(quote! {})
 }
 
-fn build_platforms_vec(assumptions_list: &[Option<proc_macro2::TokenStream>]) -> proc_macro2::TokenStream {
+fn build_platforms_vec(assumptions_list: &[Option<proc_macro2::TokenStream>], pn: &TokenStream) -> proc_macro2::TokenStream {
     let mut array_items = Vec::new();
     
     for tokens_opt in assumptions_list {
         if let Some(tokens) = tokens_opt {
-             array_items.push(transform_tokens_to_hashmap(tokens.clone()));
+             array_items.push(transform_tokens_to_assumption(tokens.clone(), pn));
         } else {
-            array_items.push(quote! { HashMap::new() });
+            array_items.push(quote! { #pn::Assumption::any_platform() });
         }
     }
     
     quote! { #(#array_items),* }
 }
 
-fn transform_tokens_to_hashmap(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    use syn::parse::Parser;
-    use syn::{punctuated::Punctuated, MetaNameValue, Token};
-
+fn transform_tokens_to_assumption(tokens: proc_macro2::TokenStream, pn: &TokenStream) -> proc_macro2::TokenStream {
     if tokens.is_empty() {
-        return quote! { std::collections::HashMap::new() };
+        return quote! { #pn::Assumption::any_platform() };
     }
 
-    let parser = Punctuated::<MetaNameValue, Token![,]>::parse_terminated;
-    
-    let args = match parser.parse2(tokens) {
-        Ok(args) => args,
+    let list = match parse2::<AssumptionList>(tokens) {
+        Ok(list) => list,
         Err(e) => return e.into_compile_error(),
     };
 
-    let mut pairs = Vec::new();
-    
-    for nv in args {
-        let key = nv.path;
-        let value = nv.value;
-
-        let key_str = key.into_token_stream().to_string().replace(" ", "");
+    let clauses = list.expr.clauses().into_iter().map(|c| clause_tokens(&c, pn));
+    let priority = list.priority.map(|p| quote! { .with_priority(#p) });
 
-        pairs.push(quote! {
-            (
-                #key_str.to_string(),
-                std::sync::Arc::new(#value) as std::sync::Arc<dyn Feature>
-            )
-        });
+    quote! {
+        #pn::Assumption::new(vec![
+            #(#clauses),*
+        ]) #priority
     }
+}
 
+// arguments of a call that may be repeated: mutable references are reborrowed, other
+// arguments cloned (shared references are `Clone`)
+fn retry_args_from_sig(sig: &Signature) -> proc_macro2::TokenStream {
+    let args: Vec<_> = sig.inputs.iter().filter_map(|arg| {
+        match arg {
+            FnArg::Typed(pat) => match pat.ty.as_ref() {
+                syn::Type::Reference(r) if r.mutability.is_some() => {
+                    let p = &pat.pat;
+                    Some(quote! { &mut *#p })
+                }
+                _ => {
+                    let p = &pat.pat;
+                    Some(quote! { ::core::clone::Clone::clone(&#p) })
+                }
+            },
+            FnArg::Receiver(_) => None,
+        }
+    }).collect();
+    quote! { #(#args),* }
+}
+
+// statements failing to compile, on the type of the argument, when an argument copied by
+// `retry_args_from_sig` is not `Clone` (`impl Trait` arguments are checked by the calls)
+fn clone_assertions(sig: &Signature) -> proc_macro2::TokenStream {
+    let assertions = sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(pat) => match pat.ty.as_ref() {
+            syn::Type::Reference(r) if r.mutability.is_some() => None,
+            syn::Type::ImplTrait(_) => None,
+            ty => Some(quote::quote_spanned! { syn::spanned::Spanned::span(ty) =>
+                fallback_on_error_arguments_must_be_clone::<#ty>();
+            }),
+        },
+        FnArg::Receiver(_) => None,
+    });
     quote! {
-        std::collections::HashMap::from([
-            #(#pairs),*
-        ])
+        fn fallback_on_error_arguments_must_be_clone<T: ::core::clone::Clone>() {}
+        #(#assertions)*
     }
 }
 
@@ -399,3 +592,17 @@ fn args_from_sig(sig: &Signature) -> proc_macro2::TokenStream {
     }).collect();
     quote! { #(#args),* }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_copied_by_fallback_chains_must_be_clone() {
+        let sig: Signature = syn::parse_quote! { fn f(&self, input: Input, data: &[u8], log: &mut Vec<u8>, f: impl Fn()) -> Result<(), E> };
+        let checks = clone_assertions(&sig).to_string().replace(' ', "");
+        assert!(checks.contains("fallback_on_error_arguments_must_be_clone::<Input>()"));
+        assert!(checks.contains("fallback_on_error_arguments_must_be_clone::<&[u8]>()"));
+        assert!(!checks.contains("Vec<u8>") && !checks.contains("Fn()"));
+    }
+}
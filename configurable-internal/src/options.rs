@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Ident, ReturnType, Signature, Token, Type};
+
+use crate::pin::PlatformPin;
+
+/// Arguments of the module attribute, `#[configurable(resolver = expr, fallback_on_error)]`,
+/// and the platform pinned at compile time.
+#[derive(Default)]
+pub(crate) struct ModuleOptions {
+    /// resolver of the dispatchers of the module, a value implementing `Resolver`
+    pub resolver: Option<Expr>,
+    /// whether functions returning a `Result` try the next variant when one fails
+    pub fallback_on_error: bool,
+    pub pin: Option<PlatformPin>,
+    /// name of the kernel module, which keys its functions in the pin
+    pub module: String,
+}
+
+/// `name = value` or a flag.
+struct ModuleOption {
+    name: Ident,
+    value: Option<Expr>,
+}
+
+impl Parse for ModuleOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if !input.peek(Token![=]) {
+            return Ok(ModuleOption { name, value: None });
+        }
+        input.parse::<Token![=]>()?;
+        Ok(ModuleOption { name, value: Some(input.parse()?) })
+    }
+}
+
+// whether a function returns a `Result` (or an alias named `Result`, such as `io::Result`); other
+// aliases are missed, and a type named `Result` must be `std::result::Result`
+fn returns_result(sig: &Signature) -> bool {
+    match &sig.output {
+        ReturnType::Type(_, ty) => matches!(ty.as_ref(), Type::Path(p) if p.path.segments.last().is_some_and(|s| s.ident == "Result")),
+        ReturnType::Default => false,
+    }
+}
+
+impl Parse for ModuleOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = ModuleOptions::default();
+        for option in Punctuated::<ModuleOption, Token![,]>::parse_terminated(input)? {
+            match (option.name.to_string().as_str(), option.value) {
+                ("resolver", Some(_)) if options.resolver.is_some() => return Err(syn::Error::new_spanned(option.name, "duplicate `resolver` option")),
+                ("resolver", Some(value)) => options.resolver = Some(value),
+                ("fallback_on_error", None) => options.fallback_on_error = true,
+                _ => return Err(syn::Error::new_spanned(option.name, "expected `resolver = ...` or `fallback_on_error`")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl ModuleOptions {
+
+    /// Expression selecting the variant among `variants`, a `Vec<Assumption>`.
+    pub fn resolve_tokens(&self, pn: &TokenStream) -> TokenStream {
+        match &self.resolver {
+            Some(resolver) => quote! { #pn::resolve_with(&#resolver, variants) },
+            None => quote! { #pn::resolve_assumptions(variants) },
+        }
+    }
+
+    /// Expression ranking the variants among `variants` (see `Resolver::rank`).
+    pub fn rank_tokens(&self, pn: &TokenStream) -> TokenStream {
+        match &self.resolver {
+            Some(resolver) => quote! { #pn::rank_with(&#resolver, variants) },
+            None => quote! { #pn::rank_assumptions(variants) },
+        }
+    }
+
+    /// Whether the dispatcher of a function tries the next variant when one returns an error.
+    pub fn chains(&self, sig: &Signature) -> bool { self.fallback_on_error && returns_result(sig) }
+
+    /// Whether compile-time ambiguity warnings apply, i.e. the module uses the installed resolver.
+    pub fn warns_ambiguity(&self) -> bool { self.resolver.is_none() }
+
+    /// The variant called on the pinned platform, if the pin knows it (modules with their own
+    /// resolver and fallback chains are resolved at run time). `function` is the name of the
+    /// function, prefixed with the type of its `impl` block for methods.
+    pub fn pinned_variant(&self, function: &str, sig: &Signature, count: usize, fallback: usize) -> Option<usize> {
+        let pin = self.pin.as_ref().filter(|_| self.resolver.is_none() && !self.chains(sig))?;
+        pin.select(&format!("{}::{function}", self.module), count).map(|i| if i < 0 { fallback } else { i as usize })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+
+    use super::*;
+
+    fn options(tokens: TokenStream) -> syn::Result<ModuleOptions> { syn::parse2(tokens) }
+
+    #[test]
+    fn parses_the_resolver() {
+        let parsed = options(quote! { resolver = configurable_features::FirstCompatible }).unwrap();
+        assert_eq!(parsed.resolver.unwrap().to_token_stream().to_string(), quote! { configurable_features::FirstCompatible }.to_string());
+
+        let parsed = options(quote! { resolver = configurable_features::FirstCompatible, fallback_on_error }).unwrap();
+        assert!(parsed.resolver.is_some() && parsed.fallback_on_error);
+
+        let parsed = options(quote! {}).unwrap();
+        assert!(parsed.resolver.is_none() && !parsed.fallback_on_error && parsed.warns_ambiguity());
+    }
+
+    #[test]
+    fn named_resolvers_resolve_through_resolve_with() {
+        let pn = quote! { configurable_features };
+        let parsed = options(quote! { resolver = Scored::new(score) }).unwrap();
+        assert!(!parsed.warns_ambiguity());
+        assert!(parsed.resolve_tokens(&pn).to_string().contains("resolve_with"));
+        assert!(ModuleOptions::default().resolve_tokens(&pn).to_string().contains("resolve_assumptions"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_duplicate_options() {
+        let message = |tokens| options(tokens).err().unwrap().to_string();
+        assert_eq!(message(quote! { resolver = A, resolver = B }), "duplicate `resolver` option");
+        assert!(message(quote! { strategy = A }).starts_with("expected"));
+        assert!(message(quote! { fallback_on_error = true }).starts_with("expected"));
+    }
+
+    #[test]
+    fn chains_functions_returning_a_result() {
+        let sig = |tokens: TokenStream| syn::parse2::<Signature>(tokens).unwrap();
+        let chaining = options(quote! { fallback_on_error }).unwrap();
+        assert!(chaining.chains(&sig(quote! { fn f(x: u32) -> Result<u32, String> })));
+        assert!(chaining.chains(&sig(quote! { fn f() -> std::io::Result<()> })));
+        assert!(!chaining.chains(&sig(quote! { fn f() -> Option<u32> })));
+        assert!(!chaining.chains(&sig(quote! { fn f() })));
+        // aliases are not resolved
+        assert!(!chaining.chains(&sig(quote! { fn f() -> Fallible<u32> })));
+        assert!(!ModuleOptions::default().chains(&sig(quote! { fn f() -> Result<(), ()> })));
+    }
+}
@@ -0,0 +1,98 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use toml::{Table, Value};
+
+/// Environment variable naming the platform pin (see `export_platform_pin`).
+const PLATFORM_PIN_VAR: &str = "CONFIGURABLE_PLATFORM_PIN";
+
+/// Platform pinned at compile time, read from the file named by `CONFIGURABLE_PLATFORM_PIN`.
+///
+/// The pin holds the variant of each kernel function selected by run-time resolution in the
+/// build script that wrote it (see `export_platform_pin` in the main crate): the macro only
+/// reads the indices.
+pub(crate) struct PlatformPin {
+    pub path: PathBuf,
+    /// selected variant (`-1`: the fallback) and number of variants, by `module::function`
+    variants: HashMap<String, (i32, usize)>,
+}
+
+impl PlatformPin {
+
+    /// The pin named by `CONFIGURABLE_PLATFORM_PIN`, `None` if it is not set.
+    pub fn from_env() -> Option<Result<PlatformPin, String>> {
+        let name = std::env::var(PLATFORM_PIN_VAR).ok().filter(|name| !name.is_empty())?;
+        let mut path = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+        path.push(name);
+        Some(PlatformPin::read(path))
+    }
+
+    fn read(path: PathBuf) -> Result<PlatformPin, String> {
+        let contents = fs::read_to_string(&path).map_err(|e| format!("failed to read platform pin {path:?}: {e}"))?;
+        PlatformPin::parse(path, &contents)
+    }
+
+    /// A pin from the contents of a pin file, `path` naming it in messages.
+    pub fn parse(path: PathBuf, contents: &str) -> Result<PlatformPin, String> {
+        let table: Table = contents.parse().map_err(|e| format!("invalid platform pin {path:?}: {e}"))?;
+        let Some(section) = table.get("variants").and_then(Value::as_table) else {
+            return Err(format!(
+                "{path:?} is not a platform pin: write one with `export_platform_pin` in a build script, \
+                 which can load a Platform.toml file first"
+            ));
+        };
+
+        let entry = |v: &Value| {
+            let v = v.as_table()?;
+            let selected = i32::try_from(v.get("selected")?.as_integer()?).ok()?;
+            let count = usize::try_from(v.get("variants")?.as_integer()?).ok()?;
+            Some((selected, count))
+        };
+        let variants = section.iter()
+            .map(|(k, v)| entry(v).map(|e| (k.clone(), e)).ok_or_else(|| format!("invalid entry `{k}` in platform pin {path:?}")))
+            .collect::<Result<_, _>>()?;
+        Ok(PlatformPin { path, variants })
+    }
+
+    /// Statement making the crate depend on the pin file, so that changing it rebuilds the crate.
+    pub fn tracking_tokens(&self) -> TokenStream {
+        let path = self.path.to_string_lossy();
+        quote! { const _: &[u8] = include_bytes!(#path); }
+    }
+
+    /// The variant selected for a kernel function with `count` variants (`-1` for the
+    /// fallback), or `None` if the pin does not know it: the build script left it to run
+    /// time, or the pin was written for other sources.
+    pub fn select(&self, key: &str, count: usize) -> Option<i32> {
+        self.variants.get(key).filter(|(_, n)| *n == count).map(|(selected, _)| *selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_selected_variants() {
+        let pin = PlatformPin::parse(PathBuf::from("pin.toml"), r#"
+            [variants]
+            "kernels::saxpy" = { selected = 2, variants = 3 }
+            "kernels::Solver::solve" = { selected = -1, variants = 2 }
+        "#).unwrap();
+        assert_eq!(pin.select("kernels::saxpy", 3), Some(2));
+        assert_eq!(pin.select("kernels::Solver::solve", 2), Some(-1));
+        // stale entries and unknown functions resolve at run time
+        assert_eq!(pin.select("kernels::saxpy", 4), None);
+        assert_eq!(pin.select("kernels::scale", 1), None);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_pins() {
+        let platform_toml = "[node]\nnode_count = 1\n";
+        assert!(PlatformPin::parse(PathBuf::from("Platform.toml"), platform_toml).err().unwrap().contains("is not a platform pin"));
+        let invalid = "[variants]\n\"kernels::saxpy\" = 2\n";
+        assert!(PlatformPin::parse(PathBuf::from("pin.toml"), invalid).err().unwrap().contains("invalid entry `kernels::saxpy`"));
+    }
+}
@@ -0,0 +1,6 @@
+// Kernels included by the `configurable!` example of the `#[configurable]` documentation.
+
+#[assumptions(acc_model = NVIDIA_GPU)]
+fn generic_kernel() {
+    println!("Running CUDA kernel");
+}
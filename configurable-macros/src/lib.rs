@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use configurable_internal::__internal_configurable;
+use configurable_internal::__internal_configurable_with;
 
 /// Marks a module as a **Kernel Module**, enabling structured platform-aware programming.
 ///
@@ -17,6 +17,18 @@ use configurable_internal::__internal_configurable;
 /// 2. **Hardware Constraints**: Use the `#[assumptions(...)]` attribute to specify requirements.
 ///    The set of platform arguments defines the assumption for implementing the k-function version,
 ///    allowing you to target specific features like SIMD sets (`cpu_simd`) or Accelerator Models (`acc_model`).
+///    An entry `key != value` excludes a feature instead: the variant is compatible only if the platform
+///    feature is not a subtype of `value` (e.g. `#[assumptions(acc_model != NVIDIA_GPU_Kepler)]`), which
+///    includes a platform reporting a supertype of `value` (`NVIDIA_GPU`) or no value for `key`.
+///    A value may also list alternatives, `acc_model = NVIDIA_GPU | AMD_GPU`, in which case the platform
+///    feature must be a subtype of at least one of them.
+///    Entries combine into boolean expressions in the style of `cfg(...)`, e.g.
+///    `#[assumptions(all(cpu_simd = AVX2, any(acc_model = NVIDIA_GPU, cores = AtLeast{val:32})))]`.
+///    A top-level list reads as `all(...)`. Expressions are normalized into disjunctive normal form;
+///    one variant is more specific than another when each of its clauses is more specific than some
+///    clause of the other.
+///    A value written as a string literal, `acc_model = "sm_80"`, names a registered feature or one of
+///    its aliases (see `feature_alias!`); an unknown name makes the entry unsatisfiable, with a warning.
 ///
 /// 3. **Fallback Requirement**: You **must** provide a fallback. The first one declared is the
 ///    fallback version, which does not declare any assumptions because it must be executed on
@@ -26,15 +38,66 @@ use configurable_internal::__internal_configurable;
 ///    At the beginning of execution, each kernel module... selects a k-function whose assumption
 ///    is compatible with the features of the underlying computing platform, prioritizing the most
 ///    specific valid assumption ($P <: K_n < ... < K_0$).
+///    When several compatible variants are incomparable (e.g. one assumes `cpu_simd = AVX512F`
+///    and another `acc_model = NVIDIA_GPU`), the choice follows the ambiguity policy
+///    (`set_ambiguity_policy`: first declared, by default last declared, or the fallback variant)
+///    and a warning is printed. Equivalent variants (e.g. the same assumption
+///    written twice) are incomparable as well. Pairs of variants that are incomparable whatever the hierarchies are reported
+///    at compile time as a `deprecated` warning.
+///    A `priority = n` entry (an `i32` expression, 0 by default) ranks incomparable variants
+///    beforehand: among the most specific compatible variants, the one with the highest priority
+///    is selected, e.g. `#[assumptions(acc_model = NVIDIA_GPU, priority = 10)]` over
+///    `#[assumptions(cpu_simd = AVX512F)]` on a node that satisfies both. Priorities never
+///    override specificity, and the ambiguity policy only applies to ties.
+///    The choice is made on the first call and cached, except inside `with_platform` scopes, where
+///    dispatchers resolve on each call against the scoped platform of the current thread (e.g. to
+///    test the selection under several platforms in one test binary).
+///
+/// 5. **Resolver**: The algorithm above is the default `Resolver`, `MostSpecific`. Another one
+///    (`FirstCompatible`, `PriorityOrdered`, `Scored`, or any implementation of the trait) can be
+///    installed for all kernel modules with `set_resolver`, before the first dispatch, or for the
+///    functions of one module with `#[configurable(resolver = expr)]`, `expr` being evaluated in
+///    the module, e.g. `#[configurable(resolver = configurable_features::FirstCompatible)]`.
+///
+/// 6. **Platform Pinning**: When building for a known platform, a build script declaring it
+///    resolves the kernel functions of the crate sources with `export_platform_pin`, which writes
+///    the selected variants to a pin file. Building with `CONFIGURABLE_PLATFORM_PIN` naming that
+///    file (relative to the crate manifest) makes each dispatcher it covers call the selected
+///    variant directly, and the other variants are not emitted. Dispatchers whose choice cannot be
+///    known by the build script (values that are not literals, priorities that are not integer
+///    literals, features or parameters it does not declare, ties left to the ambiguity policy) and
+///    modules with their own resolver or `fallback_on_error` keep run-time resolution. Pinned
+///    dispatchers ignore `set_resolver`, with a warning on their first call.
+///    Set the variable from a build script (`cargo:rustc-env=CONFIGURABLE_PLATFORM_PIN=...`) so that
+///    changing it rebuilds the crate; changes to the pin file itself are tracked. The variable must
+///    name a pin file: a Platform.toml file is rejected, the build script loads it before
+///    exporting the pin (see `export_platform_pin`).
+///
+/// 7. **Fallback on Error**: With `#[configurable(fallback_on_error)]`, the dispatchers of functions
+///    returning a `Result` rank the compatible variants (`Resolver::rank`: the selected one, then the
+///    one selected without it, and so on, ending with the fallback; ties follow the ambiguity policy,
+///    and under `Fallback` the fallback comes right after the first tie) and, when a variant returns an
+///    error, call the next one with the same arguments. Failed variants are remembered and skipped by
+///    later calls; the fallback is always tried, and its error is returned. Failures are reported as
+///    warnings, every time (see `report_variant_failure`). Arguments passed by value must be `Clone`
+///    (a compile error names the argument type otherwise), as every variant but the last one tried
+///    takes a copy (mutable references are reborrowed), and methods
+///    cannot take `self` by value. The return type must be written `Result<...>` or `path::Result<...>`
+///    (e.g. `io::Result<T>`), naming `std::result::Result`: other aliases are not recognized, and
+///    another type named `Result` is a compile error.
 ///
 /// # Examples
 ///
 /// ## 1. Standalone Functions
 /// Use `configurable` to create simple function variants (e.g., a fast SIMD version and a scalar fallback).
 ///
-/// ```rust
+/// ```no_run
+/// # use configurable_features::{configurable, create_feature_hierarchy};
+/// # create_feature_hierarchy!{register_simd ;"cpu_simd" : None :> SIMD; }
+/// # create_feature_hierarchy!{register_simd_avx ;"cpu_simd" : SIMD :> AVX2; }
 /// #[configurable]
 /// mod vector_math {
+/// #   use super::*;
 ///
 ///     // Variant 2: Standard Scalar Fallback (Required)
 ///     #[assumptions]
@@ -49,15 +112,20 @@ use configurable_internal::__internal_configurable;
 ///         vec![]
 ///     }
 /// }
+/// # fn main() {}
 /// ```
 ///
 /// ## 2. External File Inclusion
 /// To keep your code modular, you can define your kernels in separate files (e.g., one file for
 /// NVIDIA logic, one for Intel) and include them using the `configurable!` pseudo-macro.
 ///
-/// ```rust
+/// ```no_run
+/// # use configurable_features::{configurable, create_feature_hierarchy};
+/// # create_feature_hierarchy!{register_acc ;"acc_model" : None :> ACCModel; }
+/// # create_feature_hierarchy!{register_acc_nvidia ;"acc_model" : ACCModel :> NVIDIA_GPU; }
 /// #[configurable]
 /// mod kernels {
+/// #   use super::NVIDIA_GPU;
 ///
 ///     // You can mix external includes with inline definitions.
 ///     #[assumptions]
@@ -65,17 +133,23 @@ use configurable_internal::__internal_configurable;
 ///         println!("Running generic kernel");
 ///     }
 /// 
-///     // Looks for 'cuda_kernels.rs' in the same directory.
+///     // Looks for 'doc/cuda_kernels.rs' relative to the crate manifest directory.
 ///     // That file should contain functions marked with #[assumptions(...)].
-///     configurable!("cuda_kernels.rs");
+///     configurable!("doc/cuda_kernels.rs");
 /// }
+/// # fn main() {}
 /// ```
 ///
 /// ## 3. Implementation Blocks & Traits
 /// You can implement a standard Rust trait where the method implementation is chosen dynamically
 /// based on hardware features.
 ///
-/// ```rust
+/// ```no_run
+/// # use configurable_features::{configurable, create_feature_hierarchy};
+/// # create_feature_hierarchy!{register_simd ;"cpu_simd" : None :> SIMD; }
+/// # create_feature_hierarchy!{register_simd_avx ;"cpu_simd" : SIMD :> AVX2; }
+/// # create_feature_hierarchy!{register_backend ;"acc_backend" : None :> Backend; }
+/// # create_feature_hierarchy!{register_backend_cuda ;"acc_backend" : Backend :> CUDA; }
 /// trait LinearAlgebra {
 ///     fn dot_product(&self, other: &Self) -> f32;
 /// }
@@ -85,6 +159,7 @@ use configurable_internal::__internal_configurable;
 /// #[configurable]
 /// mod math_impls {
 ///     use super::{LinearAlgebra, Vector};
+/// #   use super::{AVX2, CUDA};
 ///
 ///     // Implement the trait. The macro will generate a dispatcher for `dot_product`.
 ///     impl LinearAlgebra for Vector {
@@ -112,8 +187,9 @@ use configurable_internal::__internal_configurable;
 ///         }
 ///     }
 /// }
+/// # fn main() {}
 /// ```
 #[proc_macro_attribute]
-pub fn configurable(_: TokenStream, item: TokenStream) -> TokenStream {
-    __internal_configurable(item.into(), "configurable", "assumptions", "configurable_features").into()
+pub fn configurable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    __internal_configurable_with(attr.into(), item.into(), "configurable", "assumptions", "configurable_features").into()
 }
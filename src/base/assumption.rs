@@ -0,0 +1,40 @@
+use super::{FeatureSet, PlatformFeatures};
+
+/// Assumption of a kernel variant, normalized to disjunctive normal form.
+///
+/// Each clause is a conjunction of per-parameter constraints. A platform is compatible
+/// with the assumption if it is compatible with at least one clause. A plain
+/// `key = value, ...` list is a single clause; `any(...)` expressions produce several.
+///
+/// The priority (`priority = n` in `#[assumptions(...)]`, 0 by default) ranks compatible
+/// variants none of which is more specific than the others: the highest priority wins.
+#[derive(Clone)]
+pub struct Assumption {
+    pub clauses: Vec<FeatureSet>,
+    pub priority: i32,
+}
+
+impl Assumption {
+    pub fn new<C: Into<FeatureSet>>(clauses: Vec<C>) -> Self {
+        Assumption { clauses: clauses.into_iter().map(Into::into).collect(), priority: 0 }
+    }
+
+    /// The assumption of a fallback variant, compatible with any platform.
+    pub fn any_platform() -> Self { Assumption { clauses: vec![FeatureSet::new()], priority: 0 } }
+
+    /// Whether the assumption holds on any platform (it has a clause without constraint).
+    pub fn is_unconstrained(&self) -> bool { self.clauses.iter().any(|c| c.is_empty()) }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl From<PlatformFeatures> for Assumption {
+    fn from(clause: PlatformFeatures) -> Self { Assumption { clauses: vec![clause.into()], priority: 0 } }
+}
+
+impl From<FeatureSet> for Assumption {
+    fn from(clause: FeatureSet) -> Self { Assumption { clauses: vec![clause], priority: 0 } }
+}
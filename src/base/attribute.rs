@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// Value of a key/value attribute attached to a feature, e.g. `memory_gb = 80` in
+/// `create_feature_hierarchy!`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeatureAttribute {
+    Int(i64),
+    Float(f64),
+    Str(&'static str),
+    Bool(bool),
+}
+
+impl FeatureAttribute {
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self { FeatureAttribute::Int(v) => Some(*v), _ => None }
+    }
+
+    /// Integers are widened to floats.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            FeatureAttribute::Float(v) => Some(*v),
+            FeatureAttribute::Int(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self { FeatureAttribute::Str(v) => Some(v), _ => None }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { FeatureAttribute::Bool(v) => Some(*v), _ => None }
+    }
+}
+
+impl From<i32> for FeatureAttribute {
+    fn from(v: i32) -> Self { FeatureAttribute::Int(v as i64) }
+}
+
+impl From<i64> for FeatureAttribute {
+    fn from(v: i64) -> Self { FeatureAttribute::Int(v) }
+}
+
+impl From<f64> for FeatureAttribute {
+    fn from(v: f64) -> Self { FeatureAttribute::Float(v) }
+}
+
+impl From<&'static str> for FeatureAttribute {
+    fn from(v: &'static str) -> Self { FeatureAttribute::Str(v) }
+}
+
+impl From<bool> for FeatureAttribute {
+    fn from(v: bool) -> Self { FeatureAttribute::Bool(v) }
+}
+
+impl fmt::Display for FeatureAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureAttribute::Int(v) => write!(f, "{v}"),
+            FeatureAttribute::Float(v) => write!(f, "{v}"),
+            FeatureAttribute::Str(v) => write!(f, "{v}"),
+            FeatureAttribute::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
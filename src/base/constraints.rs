@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use super::{Feature, FeatureObj};
+
+// operand of a compound feature, parenthesized when it is compound itself
+pub(crate) fn operand_string(f: &Arc<dyn Feature>) -> String {
+    match f.feature_obj() {
+        FeatureObj::Disjunction(_) | FeatureObj::Conjunction(_) => format!("({})", f.string()),
+        _ => f.string(),
+    }
+}
+
+/// Assumption value that excludes a feature (`key != value` in `#[assumptions]`).
+///
+/// A platform feature satisfies it only if it is not a subtype of the excluded feature.
+pub struct Negated(pub Arc<dyn Feature>);
+
+impl Negated {
+    pub fn new<F: Feature + 'static>(f: F) -> Self { Negated(Arc::new(f)) }
+}
+
+impl Feature for Negated {
+    fn feature_obj(&self) -> FeatureObj { FeatureObj::Negation(self.0.clone()) }
+    fn string(&self) -> String { format!("not {}", operand_string(&self.0)) }
+}
+
+/// Assumption value that accepts any of several alternatives (`key = A | B` in `#[assumptions]`).
+///
+/// A platform feature satisfies it if it is a subtype of at least one alternative.
+pub struct AnyOf(pub Vec<Arc<dyn Feature>>);
+
+impl AnyOf {
+    pub fn new(alternatives: Vec<Arc<dyn Feature>>) -> Self { AnyOf(alternatives) }
+}
+
+impl Feature for AnyOf {
+    fn feature_obj(&self) -> FeatureObj { FeatureObj::Disjunction(self.0.clone()) }
+    fn string(&self) -> String {
+        self.0.iter().map(operand_string).collect::<Vec<String>>().join(" | ")
+    }
+}
+
+/// Conjunction of assumption values on the same parameter, produced when an assumption
+/// expression constrains a parameter more than once (e.g. `all(cpu_simd = AVX2, cpu_simd != AVX512F)`).
+///
+/// A platform feature satisfies it if it satisfies every constraint.
+pub struct AllOf(pub Vec<Arc<dyn Feature>>);
+
+impl AllOf {
+    pub fn new(constraints: Vec<Arc<dyn Feature>>) -> Self { AllOf(constraints) }
+}
+
+impl Feature for AllOf {
+    fn feature_obj(&self) -> FeatureObj { FeatureObj::Conjunction(self.0.clone()) }
+    fn string(&self) -> String {
+        self.0.iter().map(operand_string).collect::<Vec<String>>().join(" & ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{AtLeast, FeatureSet, PLATFORM_PARAMETERS};
+    use crate::testing::{TestAcc, TestAmpere, TestFpga, TestGpu, TestHopper, declare_test_parameters};
+    use super::*;
+
+    fn obj<F: Feature + 'static>(f: F) -> FeatureObj { f.feature_obj() }
+
+    #[test]
+    fn negation_is_met_by_features_outside_the_excluded_one() {
+        let not_old = obj(Negated::new(TestAmpere));
+        assert!(obj(TestHopper).satisfies(&not_old));
+        assert!(obj(TestFpga).satisfies(&not_old));
+        assert!(obj(TestGpu).satisfies(&not_old));
+        assert!(!obj(TestAmpere).satisfies(&not_old));
+    }
+
+    #[test]
+    fn negation_specificity_requires_disjointness() {
+        let not_old = obj(Negated::new(TestAmpere));
+        assert!(obj(TestFpga).subtypeof(&not_old));
+        assert!(!obj(TestGpu).subtypeof(&not_old));
+        assert!(obj(Negated::new(TestGpu)).subtypeof(&not_old));
+        assert!(!not_old.subtypeof(&obj(Negated::new(TestGpu))));
+    }
+
+    #[test]
+    fn negated_alternatives_exclude_each_of_them() {
+        let neither = obj(Negated::new(AnyOf::new(vec![Arc::new(TestAmpere), Arc::new(TestFpga)])));
+        assert!(obj(TestHopper).satisfies(&neither));
+        assert!(!obj(TestFpga).satisfies(&neither));
+        assert!(!obj(TestAmpere).satisfies(&neither));
+    }
+
+    #[test]
+    fn conjunction_with_negation() {
+        let gpu_not_old = obj(AllOf::new(vec![Arc::new(TestGpu), Arc::new(Negated::new(TestAmpere))]));
+        assert!(obj(TestHopper).satisfies(&gpu_not_old));
+        assert!(!obj(TestAmpere).satisfies(&gpu_not_old));
+        assert!(!obj(TestFpga).satisfies(&gpu_not_old));
+    }
+
+    #[test]
+    fn negated_quantifier() {
+        let not_many = obj(Negated::new(AtLeast { val: 8 }));
+        assert!(obj(4).satisfies(&not_many));
+        assert!(!obj(16).satisfies(&not_many));
+    }
+
+    #[test]
+    fn missing_values_meet_exclusions() {
+        declare_test_parameters();
+        let clause = FeatureSet::new().with("test_acc", Negated::new(TestAmpere));
+        assert!(FeatureSet::new().satisfies(&clause));
+        assert!(FeatureSet::new().with("test_acc", TestHopper).satisfies(&clause));
+        assert!(!FeatureSet::new().with("test_acc", TestAmpere).satisfies(&clause));
+
+        // without a declared top, the missing value stands for the roots of the hierarchy
+        PLATFORM_PARAMETERS.lock().unwrap().push("constraints_test_untopped".to_string());
+        assert!(FeatureSet::new().satisfies(&FeatureSet::new().with("constraints_test_untopped", Negated::new(TestGpu))));
+        assert!(!FeatureSet::new().satisfies(&FeatureSet::new().with("constraints_test_untopped", Negated::new(TestAcc))));
+        assert!(!FeatureSet::new().satisfies(&FeatureSet::new().with("constraints_test_untopped", TestGpu)));
+    }
+}
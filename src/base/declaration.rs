@@ -0,0 +1,287 @@
+use std::{collections::HashMap, error::Error, fmt, sync::{Arc, Mutex}};
+
+use once_cell::sync::Lazy;
+
+use super::{Assumption, AtLeast, FEATURE_TOP, Feature, FeatureObj, PLATFORM_PARAMETERS, PlatformFeatures, PlatformParameter, insert_parameter, set_parameter_default};
+
+/// What the values of a platform parameter are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterKind {
+    /// features of a qualifier hierarchy, the top being its root
+    Qualifier,
+    /// integer quantifiers (`AtLeast`, `AtMost`, exact values)
+    Quantifier,
+    /// 0 or 1, as an integer quantifier
+    Flag,
+    /// version numbers encoded by `version_number`, as integer quantifiers
+    Version,
+}
+
+/// Typed declaration of a platform parameter, see `declare_parameter!`.
+#[derive(Clone)]
+pub struct ParameterDecl {
+    pub name: PlatformParameter,
+    pub kind: ParameterKind,
+    pub top: Option<Arc<dyn Feature>>,
+    pub default: Option<Arc<dyn Feature>>,
+    pub doc: &'static str,
+    /// module path of the declaration
+    pub origin: &'static str,
+}
+
+impl ParameterDecl {
+
+    pub fn new(name: &str, kind: ParameterKind) -> Self {
+        ParameterDecl { name: name.to_string(), kind, top: None, default: None, doc: "", origin: "" }
+    }
+
+    pub fn top(mut self, top: Arc<dyn Feature>) -> Self {
+        self.top = Some(top);
+        self
+    }
+
+    pub fn default_value(mut self, default: Arc<dyn Feature>) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    pub fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    pub fn origin(mut self, origin: &'static str) -> Self {
+        self.origin = origin;
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ParameterError {
+    /// the parameter is declared twice (first and second origin)
+    Duplicate(PlatformParameter, &'static str, &'static str),
+    /// the parameter was registered with `insert_parameter` using another top
+    Conflict(PlatformParameter, String, String),
+    /// a qualifier parameter is declared without top
+    MissingTop(PlatformParameter),
+    /// an assumption names a parameter that is not registered
+    Unknown(PlatformParameter),
+    /// an assumption gives a value that does not belong to the parameter
+    KindMismatch(PlatformParameter, ParameterKind, String),
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::Duplicate(name, first, second) => write!(f, "parameter `{name}` is declared in `{first}` and again in `{second}`"),
+            ParameterError::Conflict(name, top, other) => write!(f, "parameter `{name}` is declared with top `{other}` but registered with top `{top}`"),
+            ParameterError::MissingTop(name) => write!(f, "qualifier parameter `{name}` is declared without top"),
+            ParameterError::Unknown(name) => write!(f, "unknown platform parameter `{name}`"),
+            ParameterError::KindMismatch(name, kind, value) => write!(f, "`{value}` is not a {kind:?} value of parameter `{name}`"),
+        }
+    }
+}
+
+impl Error for ParameterError {}
+
+pub static PARAMETER_DECLARATIONS: Lazy<Mutex<HashMap<PlatformParameter, ParameterDecl>>> = Lazy::new(|| {
+    let map: HashMap<PlatformParameter, ParameterDecl> = HashMap::new();
+    Mutex::new(map)
+});
+
+/// Registers a typed parameter: its top (see `insert_parameter`), default and declaration.
+///
+/// Quantifier, flag and version parameters without top get `AtLeast { val: 0 }`.
+pub fn declare_parameter(decl: ParameterDecl) -> Result<(), ParameterError> {
+    let mut decls = PARAMETER_DECLARATIONS.lock().unwrap();
+    if let Some(previous) = decls.get(&decl.name) {
+        return Err(ParameterError::Duplicate(decl.name.clone(), previous.origin, decl.origin));
+    }
+
+    let top: Arc<dyn Feature> = match (&decl.top, decl.kind) {
+        (Some(top), _) => top.clone(),
+        (None, ParameterKind::Qualifier) => return Err(ParameterError::MissingTop(decl.name.clone())),
+        (None, _) => Arc::new(AtLeast { val: 0 }),
+    };
+
+    if let Some(registered) = FEATURE_TOP.lock().unwrap().get(&decl.name)
+        && registered.hash_code() != top.hash_code() {
+        return Err(ParameterError::Conflict(decl.name.clone(), registered.string(), top.string()));
+    }
+
+    insert_parameter(decl.name.clone(), top);
+    if let Some(default) = &decl.default {
+        set_parameter_default(decl.name.clone(), default.clone());
+    }
+    decls.insert(decl.name.clone(), decl);
+    Ok(())
+}
+
+/// Errors of the declarations made at startup (see `declare_parameter_at_startup`).
+pub static PARAMETER_ERRORS: Lazy<Mutex<Vec<ParameterError>>> = Lazy::new(|| {
+    let errors: Vec<ParameterError> = Vec::new();
+    Mutex::new(errors)
+});
+
+/// `declare_parameter` for static initializers (`declare_parameter!`, the catalog), which cannot
+/// unwind: a failing declaration is not registered, and its error is recorded. Resolution
+/// reports the recorded errors as warnings (see `report_resolution`), as the program installs
+/// its reporter after the static initializers ran.
+pub fn declare_parameter_at_startup(decl: ParameterDecl) {
+    if let Err(e) = declare_parameter(decl) {
+        PARAMETER_ERRORS.lock().unwrap().push(e);
+    }
+}
+
+// number of recorded errors already reported
+static REPORTED_ERRORS: Mutex<usize> = Mutex::new(0);
+
+/// Reports the startup errors not reported yet (see `parameter_errors`).
+pub(crate) fn report_parameter_errors() {
+    let errors = {
+        let mut reported = REPORTED_ERRORS.lock().unwrap();
+        let errors = PARAMETER_ERRORS.lock().unwrap();
+        let new: Vec<ParameterError> = errors[*reported..].to_vec();
+        *reported = errors.len();
+        new
+    };
+    for e in errors {
+        crate::report_resolution(format!("warning: {e}"));
+    }
+}
+
+/// The errors of the declarations made at startup, e.g. a parameter declared by two crates.
+pub fn parameter_errors() -> Vec<ParameterError> {
+    PARAMETER_ERRORS.lock().unwrap().clone()
+}
+
+pub fn parameter_declaration(name: &str) -> Option<ParameterDecl> {
+    PARAMETER_DECLARATIONS.lock().unwrap().get(name).cloned()
+}
+
+/// Encodes `major.minor.patch` as the integer value of a `Version` parameter.
+pub fn version_number(major: i32, minor: i32, patch: i32) -> i32 {
+    major * 1_000_000 + minor * 1_000 + patch
+}
+
+// whether a value (possibly negated or combined) fits the kind of a declared parameter
+fn value_matches(decl: &ParameterDecl, value: &FeatureObj) -> bool {
+    match value {
+        FeatureObj::Negation(f) => value_matches(decl, &f.feature_obj()),
+        FeatureObj::Disjunction(fs) | FeatureObj::Conjunction(fs) => fs.iter().all(|f| value_matches(decl, &f.feature_obj())),
+        FeatureObj::Quantifier(q) => match decl.kind {
+            ParameterKind::Qualifier => false,
+            ParameterKind::Flag => (0..=1).contains(&q.val()),
+            ParameterKind::Quantifier | ParameterKind::Version => true,
+        },
+        FeatureObj::Qualifier(_) | FeatureObj::QualifierVec(_) => decl.kind == ParameterKind::Qualifier
+            && decl.top.as_ref().is_none_or(|top| value.subtypeof(&top.feature_obj())),
+    }
+}
+
+/// Checks that the keys of a set of assumed features are registered parameters and, for
+/// declared parameters, that the values have the declared kind.
+pub fn check_assumption_keys(features: &PlatformFeatures) -> Result<(), ParameterError> {
+    let decls = PARAMETER_DECLARATIONS.lock().unwrap();
+    let parameters = PLATFORM_PARAMETERS.lock().unwrap();
+    for (key, value) in features {
+        match decls.get(key) {
+            Some(decl) if !value_matches(decl, &value.feature_obj()) => {
+                return Err(ParameterError::KindMismatch(key.clone(), decl.kind, value.string()));
+            }
+            Some(_) => {}
+            None if parameters.contains(key) => {}
+            None => return Err(ParameterError::Unknown(key.clone())),
+        }
+    }
+    Ok(())
+}
+
+pub fn check_assumption(assumption: &Assumption) -> Result<(), ParameterError> {
+    assumption.clauses.iter().try_for_each(|c| check_assumption_keys(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AtMost, FeatureSet, Negated, create_feature_hierarchy, declare_parameter, parameter_default, parameter_top};
+    use crate::testing::collect_reports;
+    use super::*;
+
+    create_feature_hierarchy!{register_declaration_test_root ;"declaration_test_net" : None :> DeclTestNet; }
+    create_feature_hierarchy!{register_declaration_test_kinds ;"declaration_test_net" : DeclTestNet :> DeclTestEthernet; }
+    create_feature_hierarchy!{register_declaration_test_other ;"declaration_test_other" : None :> DeclTestOther; }
+
+    declare_parameter!{register_declaration_test_parameters ;
+        /// Network of the test.
+        "declaration_test_net" : Qualifier = DeclTestNet;
+        "declaration_test_nodes" : Quantifier, default = AtLeast { val: 1 };
+        "declaration_test_gpudirect" : Flag;
+        "declaration_test_mpi" : Version;
+    }
+
+    // declared again at startup (in either order): recorded instead of aborting
+    declare_parameter!{register_declaration_test_duplicate ;
+        "declaration_test_nodes" : Quantifier, default = AtLeast { val: 1 };
+    }
+
+    fn clause<F: Feature + 'static>(parameter: &str, value: F) -> Assumption {
+        FeatureSet::new().with(parameter, value).into()
+    }
+
+    #[test]
+    fn declarations_register_tops_and_defaults() {
+        let decl = parameter_declaration("declaration_test_net").unwrap();
+        assert_eq!(decl.kind, ParameterKind::Qualifier);
+        assert_eq!(decl.doc, "Network of the test.");
+        assert_eq!(parameter_top("declaration_test_net").map(|f| f.string()), Some("DeclTestNet".to_string()));
+        assert_eq!(parameter_top("declaration_test_gpudirect").map(|f| f.string()), Some("atleast 0".to_string()));
+        assert_eq!(parameter_default("declaration_test_nodes").map(|f| f.string()), Some("atleast 1".to_string()));
+    }
+
+    #[test]
+    fn startup_errors_are_recorded_and_reported() {
+        assert!(parameter_errors().iter().any(|e| matches!(e, ParameterError::Duplicate(name, _, _) if name == "declaration_test_nodes")));
+
+        // once, by resolution (concurrent tests may resolve first)
+        let reported = collect_reports("declaration_test_startup_untopped", || {
+            declare_parameter_at_startup(ParameterDecl::new("declaration_test_startup_untopped", ParameterKind::Qualifier));
+            report_parameter_errors();
+            report_parameter_errors();
+        });
+        assert_eq!(reported.len(), 1);
+        assert!(parameter_errors().iter().any(|e| matches!(e, ParameterError::MissingTop(name) if name == "declaration_test_startup_untopped")));
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        assert!(matches!(
+            declare_parameter(ParameterDecl::new("declaration_test_nodes", ParameterKind::Quantifier)),
+            Err(ParameterError::Duplicate(..))
+        ));
+        assert!(matches!(
+            declare_parameter(ParameterDecl::new("declaration_test_untopped", ParameterKind::Qualifier)),
+            Err(ParameterError::MissingTop(_))
+        ));
+
+        insert_parameter("declaration_test_registered".to_string(), Arc::new(AtLeast { val: 0 }));
+        assert!(matches!(
+            declare_parameter(ParameterDecl::new("declaration_test_registered", ParameterKind::Quantifier).top(Arc::new(AtMost { val: 0 }))),
+            Err(ParameterError::Conflict(..))
+        ));
+        assert!(declare_parameter(ParameterDecl::new("declaration_test_registered", ParameterKind::Quantifier)).is_ok());
+    }
+
+    #[test]
+    fn assumptions_are_checked_against_declarations() {
+        assert!(check_assumption(&clause("declaration_test_net", DeclTestEthernet)).is_ok());
+        assert!(check_assumption(&clause("declaration_test_net", Negated::new(DeclTestEthernet))).is_ok());
+        assert!(check_assumption(&clause("declaration_test_nodes", AtLeast { val: 4 })).is_ok());
+        assert!(check_assumption(&clause("declaration_test_gpudirect", 1)).is_ok());
+        assert!(check_assumption(&clause("declaration_test_mpi", AtLeast { val: version_number(4, 1, 0) })).is_ok());
+
+        assert!(matches!(check_assumption(&clause("declaration_test_undeclared", 1)), Err(ParameterError::Unknown(_))));
+        assert!(matches!(check_assumption(&clause("declaration_test_net", DeclTestOther)), Err(ParameterError::KindMismatch(..))));
+        assert!(matches!(check_assumption(&clause("declaration_test_nodes", DeclTestEthernet)), Err(ParameterError::KindMismatch(..))));
+        assert!(matches!(check_assumption(&clause("declaration_test_gpudirect", 2)), Err(ParameterError::KindMismatch(..))));
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use super::{Feature, FeatureObj, PARAMETER_DEFAULTS, PlatformFeatures, PlatformParameter, apply_parameter_defaults, insert_parameter};
+
+pub type DerivedParameterFn = dyn Fn(&PlatformFeatures) -> Option<Arc<dyn Feature>> + Send + Sync;
+
+pub type DerivedParameters = Vec<(PlatformParameter, Arc<DerivedParameterFn>)>;
+
+pub static DERIVED_PARAMETERS: Lazy<Mutex<DerivedParameters>> = Lazy::new(|| {
+    let list: DerivedParameters = Vec::new();
+    Mutex::new(list)
+});
+
+/// Registers a parameter whose platform value is computed from the other platform features,
+/// e.g. memory per core from `memory` and `cores`.
+///
+/// The parameter is declared through `insert_parameter`, so it can be used in `#[assumptions]`
+/// like any other. `compute` returns `None` when the value cannot be derived (e.g. an input is missing).
+pub fn insert_derived_parameter<F>(fname: PlatformParameter, ftop: Arc<dyn Feature>, compute: F)
+where
+    F: Fn(&PlatformFeatures) -> Option<Arc<dyn Feature>> + Send + Sync + 'static,
+{
+    insert_parameter(fname.clone(), ftop);
+    DERIVED_PARAMETERS.lock().unwrap().push((fname, Arc::new(compute)));
+}
+
+/// Adds the derived parameters to a set of platform features.
+///
+/// Registration order is not significant (parameters registered from different ctors are
+/// registered in an unspecified order): the parameters are evaluated again until none is
+/// added, so a derived parameter may read the others as long as it returns `None` while its
+/// inputs are missing. Values already present in `features` are kept.
+pub fn evaluate_derived_parameters(features: &mut PlatformFeatures) {
+    // the closures run without holding the registry lock, as they may use the feature map
+    let derived = DERIVED_PARAMETERS.lock().unwrap().clone();
+    let mut added = true;
+    while added {
+        added = false;
+        for (par, compute) in &derived {
+            if features.contains_key(par) { continue; }
+            if let Some(v) = compute(features) {
+                features.insert(par.clone(), v);
+                added = true;
+            }
+        }
+    }
+}
+
+/// Adds the parameter defaults and the derived parameters to a set of platform features.
+///
+/// Derived parameters are computed from the platform values and the defaults of the other
+/// parameters. The default of a derived parameter only applies when it cannot be derived.
+pub fn complete_platform_features(features: &mut PlatformFeatures) {
+    let derived: Vec<PlatformParameter> = DERIVED_PARAMETERS.lock().unwrap().iter().map(|(par, _)| par.clone()).collect();
+    for (par, v) in PARAMETER_DEFAULTS.lock().unwrap().iter() {
+        if derived.contains(par) { continue; }
+        features.entry(par.clone()).or_insert_with(|| v.clone());
+    }
+    evaluate_derived_parameters(features);
+    apply_parameter_defaults(features);
+}
+
+/// Value of a quantifier parameter, if present.
+pub fn get_quantifier(features: &PlatformFeatures, par: &str) -> Option<i32> {
+    match features.get(par)?.feature_obj() {
+        FeatureObj::Quantifier(q) => Some(q.val()),
+        _ => None,
+    }
+}
+
+/// Name of the qualifier feature of a parameter, if present.
+pub fn get_qualifier(features: &PlatformFeatures, par: &str) -> Option<String> {
+    match features.get(par)?.feature_obj() {
+        FeatureObj::Qualifier(q) => Some(q.string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{AtLeast, CURRENT_FEATURES, FeatureSet, current_platform, insert_parameter_with_default, set_parameter_default, with_platform};
+    use super::*;
+
+    #[test]
+    fn derived_parameters_read_earlier_ones() {
+        insert_derived_parameter("derived_test_double".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            get_quantifier(f, "derived_test_base").map(|n| Arc::new(2 * n) as Arc<dyn Feature>)
+        });
+        insert_derived_parameter("derived_test_quadruple".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            get_quantifier(f, "derived_test_double").map(|n| Arc::new(2 * n) as Arc<dyn Feature>)
+        });
+
+        let mut features = PlatformFeatures::new();
+        evaluate_derived_parameters(&mut features);
+        assert!(!features.contains_key("derived_test_double"));
+
+        features.insert("derived_test_base".to_string(), Arc::new(3));
+        evaluate_derived_parameters(&mut features);
+        assert_eq!(get_quantifier(&features, "derived_test_double"), Some(6));
+        assert_eq!(get_quantifier(&features, "derived_test_quadruple"), Some(12));
+
+        // values present in the platform are kept
+        let mut features = PlatformFeatures::new();
+        features.insert("derived_test_base".to_string(), Arc::new(3));
+        features.insert("derived_test_double".to_string(), Arc::new(5));
+        evaluate_derived_parameters(&mut features);
+        assert_eq!(get_quantifier(&features, "derived_test_quadruple"), Some(10));
+    }
+
+    #[test]
+    fn registration_order_does_not_matter() {
+        insert_derived_parameter("derived_test_late_quadruple".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            get_quantifier(f, "derived_test_late_double").map(|n| Arc::new(2 * n) as Arc<dyn Feature>)
+        });
+        insert_derived_parameter("derived_test_late_double".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            get_quantifier(f, "derived_test_late_base").map(|n| Arc::new(2 * n) as Arc<dyn Feature>)
+        });
+
+        let mut features = PlatformFeatures::from([("derived_test_late_base".to_string(), Arc::new(3) as Arc<dyn Feature>)]);
+        evaluate_derived_parameters(&mut features);
+        assert_eq!(get_quantifier(&features, "derived_test_late_quadruple"), Some(12));
+    }
+
+    #[test]
+    fn derived_parameters_read_defaults_and_fall_back_to_their_own() {
+        insert_parameter_with_default("derived_test_memory".to_string(), Arc::new(AtLeast { val: 0 }), Arc::new(64));
+        insert_derived_parameter("derived_test_memory_per_core".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            Some(Arc::new(get_quantifier(f, "derived_test_memory")? / get_quantifier(f, "derived_test_cores")?))
+        });
+        set_parameter_default("derived_test_memory_per_core".to_string(), Arc::new(1));
+
+        let per_core = |platform: FeatureSet| with_platform(platform, || get_quantifier(&current_platform(), "derived_test_memory_per_core"));
+        assert_eq!(per_core(FeatureSet::new().with("derived_test_cores", 16)), Some(4));
+        assert_eq!(per_core(FeatureSet::new().with("derived_test_cores", 16).with("derived_test_memory", 32)), Some(2));
+        assert_eq!(per_core(FeatureSet::new()), Some(1));
+    }
+
+    #[test]
+    fn derived_parameters_may_read_current_features() {
+        // evaluated without holding the CURRENT_FEATURES lock
+        insert_derived_parameter("derived_test_reader".to_string(), Arc::new(AtLeast { val: 0 }), |f| {
+            let _current = CURRENT_FEATURES.lock().unwrap();
+            get_quantifier(f, "derived_test_source").map(|n| Arc::new(n + 1) as Arc<dyn Feature>)
+        });
+
+        let platform = FeatureSet::new().with("derived_test_source", 41);
+        assert_eq!(with_platform(platform, || get_quantifier(&current_platform(), "derived_test_reader")), Some(42));
+    }
+}
@@ -1,14 +1,16 @@
 //mod quantifier;
 
 
-use crate::{PlatformParameter, QuantifierType};
+use crate::{FeatureAttribute, PlatformParameter, QuantifierType};
+
+use super::constraints::operand_string;
 
 use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}, sync::Arc};
 
 pub enum FeatureKind { Qualifier, Quantifier }
 
 pub trait Feature: Send + Sync {
-    fn feature_obj(self:&Self) -> FeatureObj;
+    fn feature_obj(&self) -> FeatureObj;
     fn string(&self) -> String;
     fn supertype(&self) -> Option<Box<dyn Feature>> { None }
 
@@ -17,7 +19,16 @@ pub trait Feature: Send + Sync {
         self.string().hash(&mut s);
         s.finish()
     }
-    
+
+    /// Human-readable description of the feature, if any.
+    fn description(&self) -> Option<&'static str> { None }
+
+    /// Key/value metadata of the feature (e.g. `memory_gb`), in declaration order.
+    fn attributes(&self) -> Vec<(&'static str, FeatureAttribute)> { Vec::new() }
+
+    fn attribute(&self, key: &str) -> Option<FeatureAttribute> {
+        self.attributes().into_iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
 }
 
 
@@ -32,19 +43,81 @@ pub trait QuantifierFeature: Feature {
 }
 
 
+#[derive(Clone)]
 pub enum FeatureObj {
     Qualifier(Arc<dyn Feature>),
     QualifierVec(Vec<Arc<dyn QualifierFeature>>),
     Quantifier(Arc<dyn QuantifierFeature>),
+    Negation(Arc<dyn Feature>),
+    Disjunction(Vec<Arc<dyn Feature>>),
+    Conjunction(Vec<Arc<dyn Feature>>),
+}
+
+// a feature object is itself a feature, e.g. for values rebuilt from their serialized form
+impl Feature for FeatureObj {
+    fn feature_obj(&self) -> FeatureObj { self.clone() }
+
+    fn string(&self) -> String {
+        match self {
+            FeatureObj::Qualifier(f) => f.string(),
+            FeatureObj::Negation(f) => format!("not {}", operand_string(f)),
+            FeatureObj::QualifierVec(v) => v.string(),
+            FeatureObj::Quantifier(q) => q.string(),
+            FeatureObj::Disjunction(fs) => fs.iter().map(operand_string).collect::<Vec<String>>().join(" | "),
+            FeatureObj::Conjunction(fs) => fs.iter().map(operand_string).collect::<Vec<String>>().join(" & "),
+        }
+    }
+
+    fn hash_code(&self) -> u64 {
+        match self {
+            FeatureObj::Qualifier(f) => f.hash_code(),
+            FeatureObj::Quantifier(q) => q.hash_code(),
+            _ => {
+                let mut s = DefaultHasher::new();
+                self.string().hash(&mut s);
+                s.finish()
+            }
+        }
+    }
+
+    fn supertype(&self) -> Option<Box<dyn Feature>> {
+        match self {
+            FeatureObj::Qualifier(f) => f.supertype(),
+            FeatureObj::Quantifier(q) => q.supertype(),
+            _ => None,
+        }
+    }
 
+    fn description(&self) -> Option<&'static str> {
+        match self {
+            FeatureObj::Qualifier(f) => f.description(),
+            _ => None,
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, FeatureAttribute)> {
+        match self {
+            FeatureObj::Qualifier(f) => f.attributes(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl FeatureObj {
 
+    /// Specificity order of features: whether every feature described by `self` is described
+    /// by `other`. `self` excluding a feature (`not a`) is only below `not b` if `b <: a`, and
+    /// `self` is below `not b` only if it is disjoint from `b`, as needed to compare assumption
+    /// values. Platform values are tested against assumptions with `satisfies`.
     pub fn subtypeof(&self, other: &FeatureObj) -> bool {
        // if self.feature_class() != other.feature_class() { return false; }
 
         match (self, other) {
+            // every alternative on the left must be covered, one alternative on the right suffices
+            (FeatureObj::Disjunction(a), _) => a.iter().all(|f| f.feature_obj().subtypeof(other)),
+            (_, FeatureObj::Conjunction(b)) => b.iter().all(|f| self.subtypeof(&f.feature_obj())),
+            (_, FeatureObj::Disjunction(b)) => b.iter().any(|f| self.subtypeof(&f.feature_obj())),
+            (FeatureObj::Conjunction(a), _) => a.iter().any(|f| f.feature_obj().subtypeof(other)),
             (FeatureObj::Qualifier(a), FeatureObj::Qualifier(b)) => {
                 if a.hash_code() == b.hash_code() { return true; }
                 let mut s = a.supertype();
@@ -83,8 +156,57 @@ impl FeatureObj {
                     },
                 }
             }
+            // excluding a feature is more specific than excluding one of its supertypes
+            (FeatureObj::Negation(a), FeatureObj::Negation(b)) => b.feature_obj().subtypeof(&a.feature_obj()),
+            (_, FeatureObj::Negation(b)) => self.disjoint(&b.feature_obj()),
             // qualifier vs quantifier: not comparable (or define your own rule)
             _ => false,
         }
     }
+
+    /// Whether the platform value `self` meets the assumption value `assumed`. Unlike
+    /// `subtypeof`, `not b` is met by any value that is not a subtype of `b`, including a
+    /// supertype of `b` (e.g. a platform reporting `NVIDIA_GPU` for `!= NVIDIA_GPU_Kepler`).
+    pub fn satisfies(&self, assumed: &FeatureObj) -> bool {
+        match (self, assumed) {
+            (FeatureObj::Disjunction(a), _) => a.iter().all(|f| f.feature_obj().satisfies(assumed)),
+            (_, FeatureObj::Conjunction(b)) => b.iter().all(|f| self.satisfies(&f.feature_obj())),
+            (_, FeatureObj::Disjunction(b)) => b.iter().any(|f| self.satisfies(&f.feature_obj())),
+            (_, FeatureObj::Negation(b)) => !self.satisfies(&b.feature_obj()),
+            _ => self.subtypeof(assumed),
+        }
+    }
+
+    // whether no feature can be a subtype of both self and other
+    pub fn disjoint(&self, other: &FeatureObj) -> bool {
+        match (self, other) {
+            (FeatureObj::Disjunction(a), _) => a.iter().all(|f| f.feature_obj().disjoint(other)),
+            (_, FeatureObj::Disjunction(b)) => b.iter().all(|f| self.disjoint(&f.feature_obj())),
+            (FeatureObj::Conjunction(a), _) => a.iter().any(|f| f.feature_obj().disjoint(other)),
+            (_, FeatureObj::Conjunction(b)) => b.iter().any(|f| self.disjoint(&f.feature_obj())),
+            // qualifier hierarchies are trees: two features share a subtype only if one is a subtype of the other
+            (FeatureObj::Qualifier(_), FeatureObj::Qualifier(_)) => !self.subtypeof(other) && !other.subtypeof(self),
+            (FeatureObj::QualifierVec(a), _) => a.iter().all(|f| FeatureObj::Qualifier(f.clone()).disjoint(other)),
+            (_, FeatureObj::QualifierVec(b)) => b.iter().all(|f| self.disjoint(&FeatureObj::Qualifier(f.clone()))),
+            (FeatureObj::Quantifier(a), FeatureObj::Quantifier(b)) => {
+                let (lo_a, hi_a) = quantifier_bounds(a.as_ref());
+                let (lo_b, hi_b) = quantifier_bounds(b.as_ref());
+                hi_a < lo_b || hi_b < lo_a
+            }
+            (FeatureObj::Negation(a), _) => other.subtypeof(&a.feature_obj()),
+            (_, FeatureObj::Negation(b)) => self.subtypeof(&b.feature_obj()),
+            // qualifiers and quantifiers never describe the same feature
+            _ => true,
+        }
+    }
+}
+
+// interval of values admitted by a quantifier
+fn quantifier_bounds(q: &dyn QuantifierFeature) -> (i64, i64) {
+    let v = q.val() as i64;
+    match q.quantifier_type() {
+        QuantifierType::AtLeast => (v, i64::MAX),
+        QuantifierType::AtMost => (i64::MIN, v),
+        QuantifierType::ExactValue => (v, v),
+    }
 }
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, error::Error, fmt, ops::Deref, sync::Arc};
+
+use crate::featuremap::lookup_feature;
+use crate::{issubtypeof, satisfies};
+
+use super::{AllOf, AnyOf, AtLeast, AtMost, Feature, Negated, PlatformFeatures, PlatformParameter, display_features, join_features, meet_features, ordered_parameters};
+
+/// Set of features, one per platform parameter: a platform description or a clause of an assumption.
+///
+/// Dereferences to the underlying `PlatformFeatures` map, and converts from and into it.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use configurable_features::{AtLeast, FeatureSet, insert_parameter};
+///
+/// insert_parameter("cores".to_string(), Arc::new(AtLeast { val: 0 }));
+///
+/// let required = FeatureSet::new().with("cores", AtLeast { val: 8 });
+/// let platform = FeatureSet::parse("cores = 16").unwrap();
+/// assert!(platform.is_subtype_of(&required));
+/// assert!(!FeatureSet::parse("cores <= 4").unwrap().is_subtype_of(&required));
+/// ```
+#[derive(Clone, Default)]
+pub struct FeatureSet(PlatformFeatures);
+
+/// Error of `FeatureSet::parse`.
+#[derive(Debug)]
+pub struct FeatureSetParseError(String);
+
+impl fmt::Display for FeatureSetParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl Error for FeatureSetParseError {}
+
+// value of `key op value`: integers are quantifiers, names are registered qualifiers (or aliases)
+fn parse_value(op: &str, value: &str) -> Result<Arc<dyn Feature>, FeatureSetParseError> {
+    if let Ok(n) = value.parse::<i32>() {
+        return match op {
+            "=" => Ok(Arc::new(n)),
+            ">=" => Ok(Arc::new(AtLeast { val: n })),
+            "<=" => Ok(Arc::new(AtMost { val: n })),
+            _ => Ok(Arc::new(Negated(Arc::new(n)))),
+        };
+    }
+
+    let mut alternatives: Vec<Arc<dyn Feature>> = Vec::new();
+    for name in value.split('|').map(str::trim) {
+        match lookup_feature(name) {
+            Some(f) => alternatives.push(f),
+            None => return Err(FeatureSetParseError(format!("unknown feature `{name}`"))),
+        }
+    }
+    let feature: Arc<dyn Feature> = if alternatives.len() == 1 { alternatives.remove(0) } else { Arc::new(AnyOf(alternatives)) };
+
+    match op {
+        "=" => Ok(feature),
+        "!=" => Ok(Arc::new(Negated(feature))),
+        _ => Err(FeatureSetParseError(format!("`{op}` needs an integer value, found `{value}`"))),
+    }
+}
+
+impl FeatureSet {
+
+    pub fn new() -> Self { FeatureSet(HashMap::new()) }
+
+    /// Adds (or replaces) the feature of a parameter.
+    pub fn with<F: Feature + 'static>(self, parameter: &str, feature: F) -> Self {
+        self.with_feature(parameter, Arc::new(feature))
+    }
+
+    pub fn with_feature(mut self, parameter: &str, feature: Arc<dyn Feature>) -> Self {
+        self.0.insert(parameter.to_string(), feature);
+        self
+    }
+
+    /// Adds the registered qualifier `name`; unknown names are ignored, like `add_qualifier`.
+    pub fn with_named(mut self, parameter: &str, name: &str) -> Self {
+        if let Some(f) = lookup_feature(name) { self.0.insert(parameter.to_string(), f); }
+        self
+    }
+
+    pub fn insert(&mut self, parameter: &str, feature: Arc<dyn Feature>) -> Option<Arc<dyn Feature>> {
+        self.0.insert(parameter.to_string(), feature)
+    }
+
+    pub fn remove(&mut self, parameter: &str) -> Option<Arc<dyn Feature>> { self.0.remove(parameter) }
+
+    /// Parses a comma-separated list of `key = value`, `key != value`, `key >= n` and `key <= n`.
+    ///
+    /// Integer values are quantifiers (`key = n` an exact value); other values name registered
+    /// features, with `|` separating alternatives. Constraints on the same key are combined.
+    pub fn parse(s: &str) -> Result<FeatureSet, FeatureSetParseError> {
+        let mut entries: Vec<(String, Vec<Arc<dyn Feature>>)> = Vec::new();
+
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (key, op, value) = ["!=", ">=", "<=", "="].iter()
+                .find_map(|op| item.split_once(op).map(|(k, v)| (k.trim(), *op, v.trim())))
+                .ok_or_else(|| FeatureSetParseError(format!("expected `key = value`, found `{item}`")))?;
+            if key.is_empty() || value.is_empty() {
+                return Err(FeatureSetParseError(format!("expected `key = value`, found `{item}`")));
+            }
+
+            let feature = parse_value(op, value)?;
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some((_, features)) => features.push(feature),
+                None => entries.push((key.to_string(), vec![feature])),
+            }
+        }
+
+        Ok(entries.into_iter().map(|(key, mut features)| {
+            let feature: Arc<dyn Feature> = if features.len() == 1 { features.remove(0) } else { Arc::new(AllOf(features)) };
+            (key, feature)
+        }).collect())
+    }
+
+    /// Whether every platform described by `self` is described by `other` (see `issubtypeof`).
+    pub fn is_subtype_of(&self, other: &FeatureSet) -> bool { issubtypeof(&self.0, &other.0) }
+
+    /// Whether the platform `self` meets the clause `clause` (see `satisfies`).
+    pub fn satisfies(&self, clause: &FeatureSet) -> bool { satisfies(&self.0, &clause.0) }
+
+    /// The features of both sets, the features of `other` replacing those of `self`.
+    pub fn override_with(&self, other: &FeatureSet) -> FeatureSet {
+        let mut result = self.clone();
+        result.0.extend(other.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+        result
+    }
+
+    /// The constraints of both sets (see `meet_features`), or `None` if they are incompatible.
+    pub fn union(&self, other: &FeatureSet) -> Option<FeatureSet> { meet_features(&self.0, &other.0).map(FeatureSet) }
+
+    /// The features common to both sets (see `join_features`).
+    pub fn join(&self, other: &FeatureSet) -> FeatureSet { FeatureSet(join_features(&self.0, &other.0)) }
+
+    /// The entries, declared parameters first, in declaration order, then the others by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&PlatformParameter, &Arc<dyn Feature>)> {
+        ordered_parameters(&self.0).into_iter().map(|k| (k, &self.0[k]))
+    }
+
+    pub fn as_map(&self) -> &PlatformFeatures { &self.0 }
+
+    pub fn into_map(self) -> PlatformFeatures { self.0 }
+}
+
+impl Deref for FeatureSet {
+    type Target = PlatformFeatures;
+    fn deref(&self) -> &PlatformFeatures { &self.0 }
+}
+
+impl From<PlatformFeatures> for FeatureSet {
+    fn from(features: PlatformFeatures) -> Self { FeatureSet(features) }
+}
+
+impl From<FeatureSet> for PlatformFeatures {
+    fn from(features: FeatureSet) -> Self { features.0 }
+}
+
+impl FromIterator<(PlatformParameter, Arc<dyn Feature>)> for FeatureSet {
+    fn from_iter<I: IntoIterator<Item = (PlatformParameter, Arc<dyn Feature>)>>(iter: I) -> Self {
+        FeatureSet(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for FeatureSet {
+    type Item = (PlatformParameter, Arc<dyn Feature>);
+    type IntoIter = std::collections::hash_map::IntoIter<PlatformParameter, Arc<dyn Feature>>;
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl PartialEq for FeatureSet {
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+impl Eq for FeatureSet {}
+
+impl fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(&display_features(&self.0), f) }
+}
+
+impl fmt::Debug for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
@@ -7,7 +7,7 @@ use super::Feature;
 impl Feature for Vec<Arc<dyn QualifierFeature>> {
    // fn is_top(self:&Self) -> bool { false }
 
-    fn hash_code(self:&Self) -> u64 {
+    fn hash_code(&self) -> u64 {
         let mut s = std::hash::DefaultHasher::new();
         self.string().hash(&mut s);
         s.finish()
@@ -15,7 +15,7 @@ impl Feature for Vec<Arc<dyn QualifierFeature>> {
 
    // fn feature_type(self:&Self) -> super::FeatureKind { super::FeatureKind::Qualifier }
 
-    fn supertype(self:&Self) -> Option<Box<dyn Feature>> { None }
+    fn supertype(&self) -> Option<Box<dyn Feature>> { None }
 
     fn string(&self) -> String {
         let mut s = String::from("[");
@@ -30,7 +30,7 @@ impl Feature for Vec<Arc<dyn QualifierFeature>> {
     }
 
     
-    fn feature_obj(self:&Self) -> super::FeatureObj {
+    fn feature_obj(&self) -> super::FeatureObj {
         super::FeatureObj::QualifierVec(self.clone())
     }
 
@@ -38,6 +38,6 @@ impl Feature for Vec<Arc<dyn QualifierFeature>> {
 }
 
 impl QualifierFeature for Vec<Arc<dyn QualifierFeature>> {
-    fn feature_class(self:&Self) -> super::PlatformParameter { self[0].feature_class() }
+    fn feature_class(&self) -> super::PlatformParameter { self[0].feature_class() }
     
 }
\ No newline at end of file
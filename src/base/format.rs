@@ -0,0 +1,86 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::{Assumption, Feature, FeatureObj, PLATFORM_PARAMETERS, PlatformFeatures, QualifierFeature, QuantifierFeature};
+
+// features are identified by `hash_code` and printed with `string`
+macro_rules! feature_identity {
+    ($t:ty) => {
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.string()) }
+        }
+
+        impl fmt::Debug for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.string()) }
+        }
+
+        impl PartialEq for $t {
+            fn eq(&self, other: &Self) -> bool { self.hash_code() == other.hash_code() }
+        }
+
+        impl Eq for $t {}
+
+        impl Hash for $t {
+            fn hash<H: Hasher>(&self, state: &mut H) { state.write_u64(self.hash_code()) }
+        }
+    };
+}
+
+feature_identity!(dyn Feature);
+feature_identity!(dyn QualifierFeature);
+feature_identity!(dyn QuantifierFeature);
+feature_identity!(FeatureObj);
+
+/// Prints platform features as `{ key = value, ... }`, declared parameters first, in
+/// declaration order, then the others by name. The alternate form (`{:#}`) puts one
+/// entry per line.
+pub struct DisplayFeatures<'a>(pub &'a PlatformFeatures);
+
+pub fn display_features(features: &PlatformFeatures) -> DisplayFeatures<'_> { DisplayFeatures(features) }
+
+/// The keys of a set of features, declared parameters first, in declaration order.
+pub fn ordered_parameters(features: &PlatformFeatures) -> Vec<&String> {
+    let declared = PLATFORM_PARAMETERS.lock().unwrap();
+    let mut keys: Vec<&String> = features.keys().collect();
+    keys.sort_by_key(|k| (declared.iter().position(|p| p == *k).unwrap_or(usize::MAX), k.to_string()));
+    keys
+}
+
+impl fmt::Display for DisplayFeatures<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = ordered_parameters(self.0);
+        if keys.is_empty() {
+            return f.write_str("{}");
+        }
+        if f.alternate() {
+            writeln!(f, "{{")?;
+            for k in keys {
+                writeln!(f, "    {k} = {}", self.0[k])?;
+            }
+            write!(f, "}}")
+        } else {
+            let entries: Vec<String> = keys.iter().map(|k| format!("{k} = {}", self.0[*k])).collect();
+            write!(f, "{{ {} }}", entries.join(", "))
+        }
+    }
+}
+
+impl fmt::Debug for DisplayFeatures<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
+
+// clauses separated by `|`, then the priority if not 0
+impl fmt::Display for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let clauses: Vec<String> = self.clauses.iter().map(|c| display_features(c).to_string()).collect();
+        f.write_str(&clauses.join(" | "))?;
+        if self.priority != 0 {
+            write!(f, " priority {}", self.priority)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Assumption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { fmt::Display::fmt(self, f) }
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use crate::featuremap::lookup_feature;
+
+use super::{AllOf, AnyOf, AtLeast, AtMost, Feature, FeatureObj, Negated, PlatformFeatures, QualifierFeature, QuantifierFeature, QuantifierType};
+
+// registered instance of a supertype, when there is one
+fn shared(f: Box<dyn Feature>) -> Arc<dyn Feature> {
+    match lookup_feature(&f.string()) {
+        Some(registered) if registered.hash_code() == f.hash_code() => registered,
+        _ => Arc::from(f),
+    }
+}
+
+fn quantifier(lo: i64, hi: i64) -> Option<FeatureObj> {
+    let q: Arc<dyn QuantifierFeature> = match (lo, hi) {
+        (i64::MIN, i64::MAX) => return None,
+        (lo, hi) if lo == hi => Arc::new(lo as i32),
+        (lo, i64::MAX) => Arc::new(AtLeast { val: lo as i32 }),
+        (i64::MIN, hi) => Arc::new(AtMost { val: hi as i32 }),
+        (lo, hi) => return Some(FeatureObj::Conjunction(vec![
+            Arc::new(AtLeast { val: lo as i32 }),
+            Arc::new(AtMost { val: hi as i32 }),
+        ])),
+    };
+    Some(FeatureObj::Quantifier(q))
+}
+
+fn bounds(q: &dyn QuantifierFeature) -> (i64, i64) {
+    let v = q.val() as i64;
+    match q.quantifier_type() {
+        QuantifierType::AtLeast => (v, i64::MAX),
+        QuantifierType::AtMost => (i64::MIN, v),
+        QuantifierType::ExactValue => (v, v),
+    }
+}
+
+fn qualifiers(obj: &FeatureObj) -> Option<Vec<Arc<dyn QualifierFeature>>> {
+    match obj {
+        FeatureObj::QualifierVec(v) => Some(v.clone()),
+        FeatureObj::Qualifier(f) => lookup_feature(&f.string()).map(|q| vec![q]),
+        _ => None,
+    }
+}
+
+fn is_sub(a: &Arc<dyn QualifierFeature>, b: &Arc<dyn QualifierFeature>) -> bool {
+    FeatureObj::Qualifier(a.clone()).subtypeof(&FeatureObj::Qualifier(b.clone()))
+}
+
+fn flatten(obj: FeatureObj, conjunction: bool) -> Vec<Arc<dyn Feature>> {
+    match obj {
+        FeatureObj::Conjunction(fs) if conjunction => fs,
+        FeatureObj::Disjunction(fs) if !conjunction => fs,
+        other => vec![other.into_feature()],
+    }
+}
+
+impl FeatureObj {
+
+    /// Least common supertype of two features, e.g. the join of `NVIDIA_GPU_A100` and
+    /// `NVIDIA_GPU_A30` is `NVIDIA_GPU_Ampere`. `None` when only the top of the parameter
+    /// (no constraint) covers both.
+    ///
+    /// Quantifiers join to the weakest bound admitting both (`8` and `16` join to `atleast 8`),
+    /// lists of qualifiers to the qualifiers they share. Other combinations are joined as alternatives.
+    pub fn join(&self, other: &FeatureObj) -> Option<FeatureObj> {
+        if self.subtypeof(other) { return Some(other.clone()); }
+        if other.subtypeof(self) { return Some(self.clone()); }
+
+        match (self, other) {
+            (FeatureObj::Qualifier(a), FeatureObj::Qualifier(_)) => {
+                let mut s = a.supertype();
+                while let Some(sup) = s {
+                    let sup = shared(sup);
+                    if other.subtypeof(&sup.feature_obj()) { return Some(FeatureObj::Qualifier(sup)); }
+                    s = sup.supertype();
+                }
+                None
+            }
+            (FeatureObj::Quantifier(a), FeatureObj::Quantifier(b)) => {
+                let ((lo_a, hi_a), (lo_b, hi_b)) = (bounds(a.as_ref()), bounds(b.as_ref()));
+                let (lo, hi) = (lo_a.min(lo_b), hi_a.max(hi_b));
+                // two distinct exact values or upper bounds: keep the side of the bound that is usually assumed
+                match (lo, hi) {
+                    (i64::MIN, i64::MAX) => None,
+                    (lo, _) if lo != i64::MIN => quantifier(lo, i64::MAX),
+                    (_, hi) => quantifier(i64::MIN, hi),
+                }
+            }
+            (FeatureObj::QualifierVec(_), FeatureObj::Qualifier(_) | FeatureObj::QualifierVec(_))
+            | (FeatureObj::Qualifier(_), FeatureObj::QualifierVec(_)) => {
+                let (a, b) = (qualifiers(self)?, qualifiers(other)?);
+                let mut common: Vec<Arc<dyn QualifierFeature>> = a.iter().filter(|x| b.iter().any(|y| is_sub(y, x))).cloned().collect();
+                for y in b.iter().filter(|y| a.iter().any(|x| is_sub(x, y))) {
+                    if !common.iter().any(|c| c.hash_code() == y.hash_code()) { common.push(y.clone()); }
+                }
+                match common.len() {
+                    0 => None,
+                    1 => Some(FeatureObj::Qualifier(common.remove(0))),
+                    _ => Some(FeatureObj::QualifierVec(common)),
+                }
+            }
+            (FeatureObj::Quantifier(_), FeatureObj::Qualifier(_) | FeatureObj::QualifierVec(_))
+            | (FeatureObj::Qualifier(_) | FeatureObj::QualifierVec(_), FeatureObj::Quantifier(_)) => None,
+            _ => {
+                let mut alternatives = flatten(self.clone(), false);
+                alternatives.extend(flatten(other.clone(), false));
+                Some(FeatureObj::Disjunction(alternatives))
+            }
+        }
+    }
+
+    /// Greatest common subtype of two features. `None` when no feature is a subtype of both.
+    ///
+    /// Quantifiers meet to the intersection of their bounds, lists of qualifiers to their union.
+    /// Other combinations are combined as a conjunction.
+    pub fn meet(&self, other: &FeatureObj) -> Option<FeatureObj> {
+        if self.subtypeof(other) { return Some(self.clone()); }
+        if other.subtypeof(self) { return Some(other.clone()); }
+        if self.disjoint(other) { return None; }
+
+        match (self, other) {
+            // qualifier hierarchies are trees: unrelated qualifiers have no common subtype
+            (FeatureObj::Qualifier(_), FeatureObj::Qualifier(_)) => None,
+            (FeatureObj::Quantifier(a), FeatureObj::Quantifier(b)) => {
+                let ((lo_a, hi_a), (lo_b, hi_b)) = (bounds(a.as_ref()), bounds(b.as_ref()));
+                quantifier(lo_a.max(lo_b), hi_a.min(hi_b))
+            }
+            (FeatureObj::QualifierVec(_), FeatureObj::Qualifier(_) | FeatureObj::QualifierVec(_))
+            | (FeatureObj::Qualifier(_), FeatureObj::QualifierVec(_)) => {
+                let mut all = qualifiers(self)?;
+                for q in qualifiers(other)? {
+                    if !all.iter().any(|x| x.hash_code() == q.hash_code()) { all.push(q); }
+                }
+                Some(FeatureObj::QualifierVec(all))
+            }
+            _ => {
+                let mut constraints = flatten(self.clone(), true);
+                constraints.extend(flatten(other.clone(), true));
+                Some(FeatureObj::Conjunction(constraints))
+            }
+        }
+    }
+
+    /// The feature object as a platform or assumption value.
+    pub fn into_feature(self) -> Arc<dyn Feature> {
+        match self {
+            FeatureObj::Qualifier(f) => f,
+            FeatureObj::Quantifier(q) => q,
+            FeatureObj::QualifierVec(v) => Arc::new(v),
+            FeatureObj::Negation(f) => Arc::new(Negated(f)),
+            FeatureObj::Disjunction(fs) => Arc::new(AnyOf(fs)),
+            FeatureObj::Conjunction(fs) => Arc::new(AllOf(fs)),
+        }
+    }
+}
+
+/// Parameter-wise join: the features both sets are subtypes of. Parameters present on
+/// one side only, or whose values have no common supertype but the top, are left out.
+pub fn join_features(lhs: &PlatformFeatures, rhs: &PlatformFeatures) -> PlatformFeatures {
+    let mut result = PlatformFeatures::new();
+    for (p, l) in lhs {
+        if let Some(r) = rhs.get(p)
+            && let Some(j) = l.feature_obj().join(&r.feature_obj()) {
+            result.insert(p.clone(), j.into_feature());
+        }
+    }
+    result
+}
+
+/// Parameter-wise meet: the features that are subtypes of both sets, or `None` if the
+/// sets constrain some parameter incompatibly.
+pub fn meet_features(lhs: &PlatformFeatures, rhs: &PlatformFeatures) -> Option<PlatformFeatures> {
+    let mut result = lhs.clone();
+    for (p, r) in rhs {
+        let value = match lhs.get(p) {
+            Some(l) => l.feature_obj().meet(&r.feature_obj())?.into_feature(),
+            None => r.clone(),
+        };
+        result.insert(p.clone(), value);
+    }
+    Some(result)
+}
+
+/// Join of the platform descriptions of several nodes: the features every node provides,
+/// e.g. to pick kernel variants that run anywhere in a heterogeneous cluster.
+pub fn lowest_common_platform<'a, I: IntoIterator<Item = &'a PlatformFeatures>>(platforms: I) -> PlatformFeatures {
+    let mut platforms = platforms.into_iter();
+    let first = platforms.next().cloned().unwrap_or_default();
+    platforms.fold(first, |acc, p| join_features(&acc, p))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{TestA100, TestA30, TestAmpere, TestFpga, TestGpu, TestHopper};
+    use super::*;
+
+    fn obj<F: Feature + 'static>(f: F) -> FeatureObj { f.feature_obj() }
+
+    fn string(f: Option<FeatureObj>) -> Option<String> { f.map(|f| f.string()) }
+
+    #[test]
+    fn qualifiers_join_to_their_least_common_supertype() {
+        assert_eq!(string(obj(TestA100).join(&obj(TestA30))), Some("TestAmpere".to_string()));
+        assert_eq!(string(obj(TestA100).join(&obj(TestHopper))), Some("TestGpu".to_string()));
+        assert_eq!(string(obj(TestA100).join(&obj(TestGpu))), Some("TestGpu".to_string()));
+        assert_eq!(string(obj(TestA100).join(&obj(TestFpga))), Some("TestAcc".to_string()));
+    }
+
+    #[test]
+    fn qualifiers_meet_only_along_a_chain() {
+        assert_eq!(string(obj(TestA100).meet(&obj(TestGpu))), Some("TestA100".to_string()));
+        assert!(obj(TestA100).meet(&obj(TestA30)).is_none());
+        assert!(obj(TestGpu).meet(&obj(TestFpga)).is_none());
+    }
+
+    #[test]
+    fn quantifiers_join_and_meet_as_intervals() {
+        assert_eq!(string(obj(8).join(&obj(16))), Some("atleast 8".to_string()));
+        assert_eq!(string(obj(AtLeast { val: 4 }).join(&obj(AtLeast { val: 8 }))), Some("atleast 4".to_string()));
+        assert_eq!(string(obj(AtMost { val: 4 }).join(&obj(AtMost { val: 8 }))), Some("atmost 8".to_string()));
+        assert!(obj(AtMost { val: 4 }).join(&obj(AtLeast { val: 8 })).is_none());
+
+        assert_eq!(string(obj(AtLeast { val: 4 }).meet(&obj(AtLeast { val: 8 }))), Some("atleast 8".to_string()));
+        assert_eq!(string(obj(AtLeast { val: 4 }).meet(&obj(AtMost { val: 4 }))), Some("exactly 4".to_string()));
+        assert_eq!(string(obj(AtLeast { val: 4 }).meet(&obj(AtMost { val: 8 }))), Some("atleast 4 & atmost 8".to_string()));
+        assert!(obj(AtLeast { val: 8 }).meet(&obj(AtMost { val: 4 })).is_none());
+    }
+
+    #[test]
+    fn qualifier_lists_join_to_shared_qualifiers() {
+        let list = |fs: Vec<Arc<dyn QualifierFeature>>| FeatureObj::QualifierVec(fs);
+        let a = list(vec![Arc::new(TestA100), Arc::new(TestFpga)]);
+        let b = list(vec![Arc::new(TestAmpere)]);
+        assert_eq!(string(a.join(&b)), Some("TestAmpere".to_string()));
+        let c = list(vec![Arc::new(TestGpu), Arc::new(TestHopper)]);
+        assert_eq!(a.meet(&c).map(|m| qualifiers(&m).unwrap().len()), Some(4));
+    }
+
+    #[test]
+    fn feature_sets_join_and_meet_by_parameter() {
+        let node = |acc: Arc<dyn Feature>, cores: i32| PlatformFeatures::from([
+            ("test_acc".to_string(), acc),
+            ("test_cores".to_string(), Arc::new(cores) as Arc<dyn Feature>),
+        ]);
+        let a = node(Arc::new(TestA100), 32);
+        let b = node(Arc::new(TestHopper), 64);
+        let c = PlatformFeatures::from([("test_acc".to_string(), Arc::new(TestA30) as Arc<dyn Feature>)]);
+
+        let common = lowest_common_platform([&a, &b]);
+        assert_eq!(common["test_acc"].string(), "TestGpu");
+        assert_eq!(common["test_cores"].string(), "atleast 32");
+        assert_eq!(join_features(&a, &c).len(), 1);
+
+        let met = meet_features(&c, &PlatformFeatures::from([("test_acc".to_string(), Arc::new(TestGpu) as Arc<dyn Feature>)])).unwrap();
+        assert_eq!(met["test_acc"].string(), "TestA30");
+        assert!(meet_features(&a, &c).is_none());
+    }
+}
@@ -2,9 +2,27 @@ mod parameters;
 mod feature;
 mod quantifiers;
 mod featurevector;
+mod constraints;
+mod assumption;
+mod derived;
+mod attribute;
+mod declaration;
+mod format;
+mod lattice;
+mod featureset;
+#[cfg(feature = "serde")]
+mod serialize;
 
 pub use parameters::*;
 pub use feature::*;
 pub use quantifiers::*;
+pub use constraints::*;
+pub use assumption::*;
+pub use derived::*;
+pub use attribute::*;
+pub use declaration::*;
+pub use format::*;
+pub use lattice::*;
+pub use featureset::*;
 #[allow(unused_imports)]
 pub use featurevector::*;
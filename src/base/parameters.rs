@@ -20,9 +20,94 @@ pub static FEATURE_TOP: Lazy<Mutex<PlatformFeatures>> = Lazy::new(|| {
     Mutex::new(map)
 });
 
+/// Platform values assumed for parameters that the platform description does not declare.
+pub static PARAMETER_DEFAULTS: Lazy<Mutex<PlatformFeatures>> = Lazy::new(|| {
+    let map: PlatformFeatures = HashMap::new();
+    Mutex::new(map)
+});
+
+/// Declares a platform parameter with its top feature, the least specific value of the parameter.
+///
+/// An assumption that does not mention the parameter is read as assuming its top.
+/// Declaring a parameter again replaces its top.
 pub fn insert_parameter(fname:PlatformParameter, fvalue: Arc<dyn Feature>) {
     let mut dict = FEATURE_TOP.lock().unwrap();
     let mut paramlist = PLATFORM_PARAMETERS.lock().unwrap();
-    paramlist.push(fname.clone());
+    if !paramlist.contains(&fname) {
+        paramlist.push(fname.clone());
+    }
     dict.insert(fname, fvalue);
-}
\ No newline at end of file
+}
+
+/// Declares a platform parameter together with the value assumed when the platform omits it.
+pub fn insert_parameter_with_default(fname:PlatformParameter, ftop: Arc<dyn Feature>, fdefault: Arc<dyn Feature>) {
+    insert_parameter(fname.clone(), ftop);
+    set_parameter_default(fname, fdefault);
+}
+
+pub fn set_parameter_default(fname:PlatformParameter, fdefault: Arc<dyn Feature>) {
+    PARAMETER_DEFAULTS.lock().unwrap().insert(fname, fdefault);
+}
+
+pub fn parameter_top(fname: &str) -> Option<Arc<dyn Feature>> {
+    FEATURE_TOP.lock().unwrap().get(fname).cloned()
+}
+
+pub fn parameter_default(fname: &str) -> Option<Arc<dyn Feature>> {
+    PARAMETER_DEFAULTS.lock().unwrap().get(fname).cloned()
+}
+
+/// Adds the default value of every parameter the platform features do not declare.
+pub fn apply_parameter_defaults(features: &mut PlatformFeatures) {
+    for (p, v) in PARAMETER_DEFAULTS.lock().unwrap().iter() {
+        features.entry(p.clone()).or_insert_with(|| v.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AtLeast, FeatureSet, current_platform, issubtypeof, with_platform};
+    use crate::testing::{TestAcc, TestGpu, declare_test_parameters};
+    use super::*;
+
+    #[test]
+    fn missing_values_take_the_top() {
+        declare_test_parameters();
+        let assumes = |f: Arc<dyn Feature>| FeatureSet::from_iter([("test_acc".to_string(), f)]);
+
+        // a platform without the parameter is the top
+        assert!(FeatureSet::new().satisfies(&assumes(Arc::new(TestAcc))));
+        assert!(!FeatureSet::new().satisfies(&assumes(Arc::new(TestGpu))));
+
+        // a clause without the parameter assumes the top
+        assert!(issubtypeof(&PlatformFeatures::new(), &assumes(Arc::new(TestAcc))));
+        assert!(!issubtypeof(&PlatformFeatures::new(), &assumes(Arc::new(TestGpu))));
+        assert!(issubtypeof(&assumes(Arc::new(TestGpu)), &PlatformFeatures::new()));
+    }
+
+    #[test]
+    fn declaring_again_replaces_the_top() {
+        insert_parameter("parameters_test_level".to_string(), Arc::new(AtLeast { val: 0 }));
+        insert_parameter("parameters_test_level".to_string(), Arc::new(AtLeast { val: 2 }));
+        assert_eq!(parameter_top("parameters_test_level").map(|f| f.string()), Some("atleast 2".to_string()));
+        assert_eq!(PLATFORM_PARAMETERS.lock().unwrap().iter().filter(|p| *p == "parameters_test_level").count(), 1);
+    }
+
+    #[test]
+    fn defaults_fill_missing_values() {
+        insert_parameter_with_default("parameters_test_memory".to_string(), Arc::new(AtLeast { val: 0 }), Arc::new(16));
+        assert_eq!(parameter_default("parameters_test_memory").map(|f| f.string()), Some("exactly 16".to_string()));
+
+        let mut features = PlatformFeatures::new();
+        apply_parameter_defaults(&mut features);
+        assert_eq!(features["parameters_test_memory"].string(), "exactly 16");
+
+        let mut features = PlatformFeatures::from([("parameters_test_memory".to_string(), Arc::new(8) as Arc<dyn Feature>)]);
+        apply_parameter_defaults(&mut features);
+        assert_eq!(features["parameters_test_memory"].string(), "exactly 8");
+
+        let large = FeatureSet::new().with("parameters_test_memory", AtLeast { val: 12 });
+        with_platform(FeatureSet::new(), || assert!(current_platform().satisfies(&large)));
+        with_platform(FeatureSet::new().with("parameters_test_memory", 8), || assert!(!current_platform().satisfies(&large)));
+    }
+}
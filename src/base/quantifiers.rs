@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{Feature, FeatureKind, PlatformParameter, QuantifierFeature};
+use crate::{Feature, QuantifierFeature};
 
 pub enum QuantifierType {
     AtLeast,
@@ -18,37 +18,37 @@ pub struct AtMost {
 
 
 impl Feature for AtLeast {
-    fn feature_obj(self:&Self) -> super::FeatureObj { super::FeatureObj::Quantifier(Arc::new(AtLeast { val: self.val })) }
-    fn string(self:&Self) -> String { format!("atleast {}", self.val) }
-    fn supertype(self:&Self) -> Option<Box<dyn Feature>> {  Some(Box::new(AtLeast { val:self.val+1 }) as Box<dyn Feature>)  }
+    fn feature_obj(&self) -> super::FeatureObj { super::FeatureObj::Quantifier(Arc::new(AtLeast { val: self.val })) }
+    fn string(&self) -> String { format!("atleast {}", self.val) }
+    fn supertype(&self) -> Option<Box<dyn Feature>> {  Some(Box::new(AtLeast { val:self.val+1 }) as Box<dyn Feature>)  }
 }
 
 impl QuantifierFeature for AtLeast {  
-    fn val(self:&Self) -> i32 { (*self).val } 
-    fn quantifier_type(self:&Self) -> QuantifierType { QuantifierType::AtLeast }    
+    fn val(&self) -> i32 { self.val } 
+    fn quantifier_type(&self) -> QuantifierType { QuantifierType::AtLeast }    
 }
 
 impl Feature for AtMost {
-    fn feature_obj(self:&Self) -> super::FeatureObj {
+    fn feature_obj(&self) -> super::FeatureObj {
         super::FeatureObj::Quantifier(Arc::new(AtMost { val: self.val }))
     }
-    fn string(self:&Self) -> String { format!("atmost {}", self.val) }
-    fn supertype(self:&Self) -> Option<Box<dyn Feature>> {  Some(Box::new(AtMost { val:self.val-1 }) )  }
+    fn string(&self) -> String { format!("atmost {}", self.val) }
+    fn supertype(&self) -> Option<Box<dyn Feature>> {  Some(Box::new(AtMost { val:self.val-1 }) )  }
 }
 
 
 impl QuantifierFeature for AtMost {  
-    fn val(self:&Self) -> i32 { (*self).val } 
-    fn quantifier_type(self:&Self) -> QuantifierType { QuantifierType::AtMost }
+    fn val(&self) -> i32 { self.val } 
+    fn quantifier_type(&self) -> QuantifierType { QuantifierType::AtMost }
 }
 
 impl Feature for i32 {
-    fn feature_obj(self:&Self) -> super::FeatureObj { super::FeatureObj::Quantifier(Arc::new(*self)) }
-    fn string(self:&Self) -> String { format!("exactly {self}") } 
+    fn feature_obj(&self) -> super::FeatureObj { super::FeatureObj::Quantifier(Arc::new(*self)) }
+    fn string(&self) -> String { format!("exactly {self}") } 
     
 }
 
 impl QuantifierFeature for i32 {  
-    fn val(self:&Self) -> i32 { *self } 
-    fn quantifier_type(self:&Self) -> QuantifierType { QuantifierType::ExactValue }
+    fn val(&self) -> i32 { *self } 
+    fn quantifier_type(&self) -> QuantifierType { QuantifierType::ExactValue }
 }
@@ -0,0 +1,248 @@
+//! Serde support (`serde` cargo feature).
+//!
+//! A feature is written as a map with a single tag:
+//!
+//! | feature                  | form                                  |
+//! |--------------------------|---------------------------------------|
+//! | qualifier                | `{ "qualifier": "NVIDIA_GPU_A100" }`  |
+//! | list of qualifiers       | `{ "qualifiers": ["CUDA", "OpenCL"] }`|
+//! | `AtLeast` / `AtMost`     | `{ "atleast": 8 }`, `{ "atmost": 8 }` |
+//! | exact value              | `{ "exactly": 8 }`                    |
+//! | `Negated`                | `{ "not": <feature> }`                |
+//! | `AnyOf` / `AllOf`        | `{ "any": [...] }`, `{ "all": [...] }`|
+//!
+//! Qualifiers are read back through the feature map (aliases included), so they must be
+//! registered in the reading program. A `PlatformFeatures` map or `FeatureSet` is a map from
+//! parameter names to features, and an `Assumption` is the list of its clauses, or the map
+//! `{ "clauses": [...], "priority": n }` if its priority is not 0. Resolution results
+//! (`Resolution`, `ResolutionTrace`, `Ambiguity`) derive both traits from these.
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::featuremap::lookup_feature;
+
+use super::{Assumption, AtLeast, AtMost, Feature, FeatureObj, FeatureSet, PlatformFeatures, QualifierFeature, QuantifierType};
+
+const TAGS: &[&str] = &["qualifier", "qualifiers", "atleast", "atmost", "exactly", "not", "any", "all"];
+
+fn tagged<S: Serializer, T: Serialize + ?Sized>(serializer: S, tag: &str, value: &T) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry(tag, value)?;
+    map.end()
+}
+
+impl Serialize for FeatureObj {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FeatureObj::Qualifier(f) => tagged(serializer, "qualifier", &f.string()),
+            FeatureObj::QualifierVec(v) => tagged(serializer, "qualifiers", &v.iter().map(|f| f.string()).collect::<Vec<String>>()),
+            FeatureObj::Quantifier(q) => match q.quantifier_type() {
+                QuantifierType::AtLeast => tagged(serializer, "atleast", &q.val()),
+                QuantifierType::AtMost => tagged(serializer, "atmost", &q.val()),
+                QuantifierType::ExactValue => tagged(serializer, "exactly", &q.val()),
+            },
+            FeatureObj::Negation(f) => tagged(serializer, "not", f),
+            FeatureObj::Disjunction(fs) => tagged(serializer, "any", fs),
+            FeatureObj::Conjunction(fs) => tagged(serializer, "all", fs),
+        }
+    }
+}
+
+impl Serialize for dyn Feature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.feature_obj().serialize(serializer)
+    }
+}
+
+fn qualifier<E: de::Error>(name: &str) -> Result<Arc<dyn QualifierFeature>, E> {
+    lookup_feature(name).ok_or_else(|| E::custom(format!("unknown feature `{name}`")))
+}
+
+struct FeatureObjVisitor;
+
+impl<'de> Visitor<'de> for FeatureObjVisitor {
+    type Value = FeatureObj;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map with a single feature tag")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FeatureObj, A::Error> {
+        let tag: String = map.next_key()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let obj = match tag.as_str() {
+            "qualifier" => FeatureObj::Qualifier(qualifier::<A::Error>(&map.next_value::<String>()?)?),
+            "qualifiers" => FeatureObj::QualifierVec(
+                map.next_value::<Vec<String>>()?.iter().map(|name| qualifier(name)).collect::<Result<_, _>>()?
+            ),
+            "atleast" => FeatureObj::Quantifier(Arc::new(AtLeast { val: map.next_value()? })),
+            "atmost" => FeatureObj::Quantifier(Arc::new(AtMost { val: map.next_value()? })),
+            "exactly" => FeatureObj::Quantifier(Arc::new(map.next_value::<i32>()?)),
+            "not" => FeatureObj::Negation(map.next_value()?),
+            "any" => FeatureObj::Disjunction(map.next_value()?),
+            "all" => FeatureObj::Conjunction(map.next_value()?),
+            other => return Err(de::Error::unknown_variant(other, TAGS)),
+        };
+        if map.next_key::<IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+        Ok(obj)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureObj {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(FeatureObjVisitor)
+    }
+}
+
+// also gives `Arc<dyn Feature>` and `PlatformFeatures` (serde `rc`)
+impl<'de> Deserialize<'de> for Box<dyn Feature> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FeatureObj::deserialize(deserializer).map(|obj| Box::new(obj) as Box<dyn Feature>)
+    }
+}
+
+impl Serialize for FeatureSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_map().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PlatformFeatures::deserialize(deserializer).map(FeatureSet::from)
+    }
+}
+
+impl Serialize for Assumption {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.priority == 0 {
+            return self.clauses.serialize(serializer);
+        }
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("clauses", &self.clauses)?;
+        map.serialize_entry("priority", &self.priority)?;
+        map.end()
+    }
+}
+
+struct AssumptionVisitor;
+
+impl<'de> Visitor<'de> for AssumptionVisitor {
+    type Value = Assumption;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of clauses or a map with `clauses` and `priority`")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Assumption, A::Error> {
+        let mut clauses: Vec<FeatureSet> = Vec::new();
+        while let Some(clause) = seq.next_element()? {
+            clauses.push(clause);
+        }
+        Ok(Assumption::new(clauses))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Assumption, A::Error> {
+        let mut clauses: Option<Vec<FeatureSet>> = None;
+        let mut priority = 0;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "clauses" => clauses = Some(map.next_value()?),
+                "priority" => priority = map.next_value()?,
+                other => return Err(de::Error::unknown_field(other, &["clauses", "priority"])),
+            }
+        }
+        let clauses = clauses.ok_or_else(|| de::Error::missing_field("clauses"))?;
+        Ok(Assumption::new(clauses).with_priority(priority))
+    }
+}
+
+impl<'de> Deserialize<'de> for Assumption {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(AssumptionVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AllOf, AmbiguityPolicy, AnyOf, Ambiguity, Negated, Resolution, resolve_all_on};
+    use crate::testing::{TestAcc, TestFpga, TestGpu, declare_test_parameters};
+    use super::*;
+
+    // toml documents are tables
+    #[derive(Serialize, Deserialize)]
+    struct Doc<T> { value: T }
+
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: T) -> (String, T) {
+        let text = toml::to_string(&Doc { value }).unwrap();
+        let doc: Doc<T> = toml::from_str(&text).unwrap();
+        (text, doc.value)
+    }
+
+    fn feature_round_trip(f: Arc<dyn Feature>) {
+        let (text, back) = round_trip(f.clone());
+        assert!(*back == *f, "{text}");
+    }
+
+    #[test]
+    fn features_round_trip() {
+        feature_round_trip(Arc::new(TestGpu));
+        feature_round_trip(Arc::new(AtLeast { val: 8 }));
+        feature_round_trip(Arc::new(AtMost { val: 2 }));
+        feature_round_trip(Arc::new(16));
+        feature_round_trip(Arc::new(Negated::new(TestGpu)));
+        feature_round_trip(Arc::new(AnyOf::new(vec![Arc::new(TestGpu), Arc::new(TestFpga)])));
+        feature_round_trip(Arc::new(AllOf::new(vec![Arc::new(TestAcc), Arc::new(Negated::new(TestFpga))])));
+        feature_round_trip(Arc::new(vec![Arc::new(TestGpu) as Arc<dyn QualifierFeature>, Arc::new(TestFpga)]));
+
+        assert_eq!(toml::to_string(&Doc { value: Arc::new(AtLeast { val: 8 }) as Arc<dyn Feature> }).unwrap().trim(), "[value]\natleast = 8");
+    }
+
+    #[test]
+    fn unknown_or_malformed_features_are_rejected() {
+        assert!(toml::from_str::<Doc<Arc<dyn Feature>>>("value = { qualifier = \"TestMissing\" }").is_err());
+        assert!(toml::from_str::<Doc<Arc<dyn Feature>>>("value = { bigger = 3 }").is_err());
+        assert!(toml::from_str::<Doc<Arc<dyn Feature>>>("value = { atleast = 3, atmost = 4 }").is_err());
+    }
+
+    #[test]
+    fn assumptions_round_trip() {
+        let clause = FeatureSet::new().with("test_acc", TestGpu).with("test_cores", AtLeast { val: 4 });
+        let plain = Assumption::new(vec![clause.clone(), FeatureSet::new().with("test_acc", TestFpga)]);
+
+        let (text, back) = round_trip(plain.clone());
+        assert_eq!(back.clauses, plain.clauses, "{text}");
+        assert_eq!(back.priority, 0);
+
+        let (text, back) = round_trip(Assumption::new(vec![clause.clone()]).with_priority(3));
+        assert!(text.contains("priority = 3"), "{text}");
+        assert_eq!((back.clauses, back.priority), (vec![clause], 3));
+    }
+
+    #[test]
+    fn resolution_results_round_trip() {
+        declare_test_parameters();
+        let list = vec![
+            Assumption::any_platform(),
+            FeatureSet::new().with("test_acc", TestGpu).into(),
+        ];
+        let resolution = resolve_all_on(&FeatureSet::new().with("test_acc", TestGpu), &list);
+        let (text, back): (String, Resolution) = round_trip(resolution.clone());
+        assert_eq!(back.indices(), vec![1, 0], "{text}");
+        assert_eq!(back.candidate(0).unwrap().dominated_by, vec![1]);
+
+        let ambiguity = Ambiguity { candidates: vec![0, 1], assumptions: list };
+        let (_, back) = round_trip(ambiguity);
+        assert_eq!(back.candidates, vec![0, 1]);
+        assert!(back.assumptions[0].is_unconstrained());
+
+        let (_, back) = round_trip(AmbiguityPolicy::FirstDeclared);
+        assert_eq!(back, AmbiguityPolicy::FirstDeclared);
+    }
+}
@@ -0,0 +1,32 @@
+//! `acc_backend`: programming models and runtimes used to drive accelerators.
+
+use crate::{create_feature_hierarchy, declare_parameter};
+
+create_feature_hierarchy!{register_catalog_acc_backend_root ;"acc_backend" : None :> ACCBackend; }
+create_feature_hierarchy!{register_catalog_acc_backend ;"acc_backend" : ACCBackend :> CUDA & 
+                                                                                     ROCm & 
+                                                                                     SYCL & 
+                                                                                     OpenCL; 
+                         }
+create_feature_hierarchy!{register_catalog_acc_backend_sycl ;"acc_backend" : SYCL :> OneAPI & 
+                                                                                    AdaptiveCpp; 
+                         }
+
+declare_parameter!{register_catalog_acc_backend_parameter ;
+    /// Programming model or runtime used to drive the accelerators.
+    "acc_backend" : Qualifier = ACCBackend;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Feature;
+    use super::*;
+
+    #[test]
+    fn sycl_implementations_are_sycl_backends() {
+        assert!(OneAPI.feature_obj().subtypeof(&SYCL.feature_obj()));
+        assert!(AdaptiveCpp.feature_obj().satisfies(&ACCBackend.feature_obj()));
+        assert!(!CUDA.feature_obj().satisfies(&SYCL.feature_obj()));
+        assert!(ROCm.feature_obj().disjoint(&CUDA.feature_obj()));
+    }
+}
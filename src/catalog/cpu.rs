@@ -0,0 +1,65 @@
+//! `cpu_vendor` and `cpu_microarch`: processor vendors and microarchitectures.
+
+use crate::{create_feature_hierarchy, declare_parameter};
+
+create_feature_hierarchy!{register_catalog_cpu_vendor_root ;"cpu_vendor" : None :> CPUVendor; }
+create_feature_hierarchy!{register_catalog_cpu_vendor ;"cpu_vendor" : CPUVendor :> Intel & 
+                                                                                  AMD & 
+                                                                                  ARM & 
+                                                                                  Apple & 
+                                                                                  AmpereComputing & 
+                                                                                  AWS & 
+                                                                                  Fujitsu & 
+                                                                                  NVIDIA; 
+                         }
+
+create_feature_hierarchy!{register_catalog_cpu_microarch_root ;"cpu_microarch" : None :> CPUMicroarchitecture; }
+create_feature_hierarchy!{register_catalog_cpu_microarch_family ;"cpu_microarch" : CPUMicroarchitecture :> Intel_Microarchitecture & 
+                                                                                                          AMD_Microarchitecture & 
+                                                                                                          ARM_Microarchitecture; 
+                         }
+create_feature_hierarchy!{register_catalog_cpu_microarch_intel ;"cpu_microarch" : Intel_Microarchitecture :> Intel_Skylake & 
+                                                                                                            Intel_CascadeLake & 
+                                                                                                            Intel_IceLake & 
+                                                                                                            Intel_SapphireRapids & 
+                                                                                                            Intel_EmeraldRapids & 
+                                                                                                            Intel_GraniteRapids; 
+                         }
+create_feature_hierarchy!{register_catalog_cpu_microarch_amd ;"cpu_microarch" : AMD_Microarchitecture :> AMD_Zen & 
+                                                                                                        AMD_Zen2 & 
+                                                                                                        AMD_Zen3 & 
+                                                                                                        AMD_Zen4 & 
+                                                                                                        AMD_Zen5; 
+                         }
+create_feature_hierarchy!{register_catalog_cpu_microarch_arm ;"cpu_microarch" : ARM_Microarchitecture :> ARM_NeoverseN1 & 
+                                                                                                        ARM_NeoverseN2 & 
+                                                                                                        ARM_NeoverseV1 & 
+                                                                                                        ARM_NeoverseV2 & 
+                                                                                                        Fujitsu_A64FX & 
+                                                                                                        Apple_M1 & 
+                                                                                                        Apple_M2 & 
+                                                                                                        Apple_M3; 
+                         }
+
+declare_parameter!{register_catalog_cpu_parameters ;
+    /// Vendor of the CPU.
+    "cpu_vendor" : Qualifier = CPUVendor;
+    /// Microarchitecture of the CPU cores.
+    "cpu_microarch" : Qualifier = CPUMicroarchitecture;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Feature, QualifierFeature};
+    use super::*;
+
+    #[test]
+    fn microarchitectures_belong_to_their_family() {
+        assert!(Intel_SapphireRapids.feature_obj().subtypeof(&Intel_Microarchitecture.feature_obj()));
+        assert!(AMD_Zen4.feature_obj().subtypeof(&AMD_Microarchitecture.feature_obj()));
+        assert!(Apple_M2.feature_obj().subtypeof(&ARM_Microarchitecture.feature_obj()));
+        assert!(AMD_Zen4.feature_obj().disjoint(&Intel_Microarchitecture.feature_obj()));
+        assert!(Intel.feature_obj().subtypeof(&CPUVendor.feature_obj()));
+        assert_eq!((NVIDIA.feature_class(), Fujitsu_A64FX.feature_class()), ("cpu_vendor".to_string(), "cpu_microarch".to_string()));
+    }
+}
@@ -0,0 +1,94 @@
+//! `acc_model`: GPU vendors, architectures and models.
+//!
+//! Models carry `memory_gb` and, for NVIDIA, the compute capability `sm` as attributes.
+
+use crate::{create_feature_hierarchy, declare_parameter};
+
+create_feature_hierarchy!{register_catalog_acc_model_root ;"acc_model" : None :> ACCModel; }
+create_feature_hierarchy!{register_catalog_acc_model_vendor ;"acc_model" : ACCModel :> NVIDIA_GPU & 
+                                                                                       AMD_GPU & 
+                                                                                       Intel_GPU; 
+                         }
+
+create_feature_hierarchy!{register_catalog_nvidia_arch ;"acc_model" : NVIDIA_GPU :> NVIDIA_GPU_Volta & 
+                                                                                  NVIDIA_GPU_Turing & 
+                                                                                  NVIDIA_GPU_Ampere & 
+                                                                                  NVIDIA_GPU_Ada & 
+                                                                                  NVIDIA_GPU_Hopper & 
+                                                                                  NVIDIA_GPU_Blackwell; 
+                         }
+create_feature_hierarchy!{register_catalog_nvidia_volta ;"acc_model" : NVIDIA_GPU_Volta :> NVIDIA_GPU_V100 { sm = 70, memory_gb = 32 }; }
+create_feature_hierarchy!{register_catalog_nvidia_turing ;"acc_model" : NVIDIA_GPU_Turing :> NVIDIA_GPU_T4 { sm = 75, memory_gb = 16 } & 
+                                                                                            NVIDIA_GPU_RTX2080Ti { sm = 75, memory_gb = 11 }; 
+                         }
+create_feature_hierarchy!{register_catalog_nvidia_ampere ;"acc_model" : NVIDIA_GPU_Ampere :> NVIDIA_GPU_A100 { sm = 80, memory_gb = 80 } & 
+                                                                                            NVIDIA_GPU_A30 { sm = 80, memory_gb = 24 } & 
+                                                                                            NVIDIA_GPU_A10 { sm = 86, memory_gb = 24 } & 
+                                                                                            NVIDIA_GPU_RTX3090 { sm = 86, memory_gb = 24 }; 
+                         }
+create_feature_hierarchy!{register_catalog_nvidia_ada ;"acc_model" : NVIDIA_GPU_Ada :> NVIDIA_GPU_L4 { sm = 89, memory_gb = 24 } & 
+                                                                                      NVIDIA_GPU_L40S { sm = 89, memory_gb = 48 } & 
+                                                                                      NVIDIA_GPU_RTX4090 { sm = 89, memory_gb = 24 } & 
+                                                                                      NVIDIA_GPU_RTX4000 { sm = 89, memory_gb = 20 }; 
+                         }
+create_feature_hierarchy!{register_catalog_nvidia_hopper ;"acc_model" : NVIDIA_GPU_Hopper :> NVIDIA_GPU_H100 { sm = 90, memory_gb = 80 } & 
+                                                                                            NVIDIA_GPU_H200 { sm = 90, memory_gb = 141 } & 
+                                                                                            NVIDIA_GPU_GH200 { sm = 90, memory_gb = 96 }; 
+                         }
+create_feature_hierarchy!{register_catalog_nvidia_blackwell ;"acc_model" : NVIDIA_GPU_Blackwell :> NVIDIA_GPU_B200 { sm = 100, memory_gb = 192 } & 
+                                                                                                  NVIDIA_GPU_GB200 { sm = 100 } & 
+                                                                                                  NVIDIA_GPU_RTX5090 { sm = 120, memory_gb = 32 }; 
+                         }
+
+create_feature_hierarchy!{register_catalog_amd_arch ;"acc_model" : AMD_GPU :> AMD_GPU_CDNA & 
+                                                                            AMD_GPU_CDNA2 & 
+                                                                            AMD_GPU_CDNA3 & 
+                                                                            AMD_GPU_RDNA2 & 
+                                                                            AMD_GPU_RDNA3; 
+                         }
+create_feature_hierarchy!{register_catalog_amd_cdna ;"acc_model" : AMD_GPU_CDNA :> AMD_GPU_MI100 { memory_gb = 32 }; }
+create_feature_hierarchy!{register_catalog_amd_cdna2 ;"acc_model" : AMD_GPU_CDNA2 :> AMD_GPU_MI210 { memory_gb = 64 } & 
+                                                                                    AMD_GPU_MI250X { memory_gb = 128 }; 
+                         }
+create_feature_hierarchy!{register_catalog_amd_cdna3 ;"acc_model" : AMD_GPU_CDNA3 :> AMD_GPU_MI300A { memory_gb = 128 } & 
+                                                                                    AMD_GPU_MI300X { memory_gb = 192 }; 
+                         }
+create_feature_hierarchy!{register_catalog_amd_rdna2 ;"acc_model" : AMD_GPU_RDNA2 :> AMD_GPU_RX6900XT; }
+create_feature_hierarchy!{register_catalog_amd_rdna3 ;"acc_model" : AMD_GPU_RDNA3 :> AMD_GPU_RX7900XTX; }
+
+create_feature_hierarchy!{register_catalog_intel_arch ;"acc_model" : Intel_GPU :> Intel_GPU_Xe_LP & 
+                                                                                Intel_GPU_Xe_HPG & 
+                                                                                Intel_GPU_Xe_HPC; 
+                         }
+create_feature_hierarchy!{register_catalog_intel_hpg ;"acc_model" : Intel_GPU_Xe_HPG :> Intel_GPU_ArcA770; }
+create_feature_hierarchy!{register_catalog_intel_hpc ;"acc_model" : Intel_GPU_Xe_HPC :> Intel_GPU_Max1100 { memory_gb = 48 } & 
+                                                                                      Intel_GPU_Max1550 { memory_gb = 128 }; 
+                         }
+
+declare_parameter!{register_catalog_acc_model_parameter ;
+    /// Accelerator model, architecture or vendor.
+    "acc_model" : Qualifier = ACCModel;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Feature;
+    use super::*;
+
+    #[test]
+    fn models_belong_to_their_architecture_and_vendor() {
+        assert!(NVIDIA_GPU_A100.feature_obj().subtypeof(&NVIDIA_GPU_Ampere.feature_obj()));
+        assert!(NVIDIA_GPU_H100.feature_obj().subtypeof(&NVIDIA_GPU.feature_obj()));
+        assert!(AMD_GPU_MI300X.feature_obj().subtypeof(&AMD_GPU_CDNA3.feature_obj()));
+        assert!(Intel_GPU_Max1550.feature_obj().subtypeof(&Intel_GPU.feature_obj()));
+        assert!(!NVIDIA_GPU_A100.feature_obj().satisfies(&NVIDIA_GPU_Hopper.feature_obj()));
+        assert!(AMD_GPU_MI250X.feature_obj().disjoint(&NVIDIA_GPU.feature_obj()));
+    }
+
+    #[test]
+    fn models_carry_their_attributes() {
+        assert_eq!(NVIDIA_GPU_H100.attribute("sm").and_then(|v| v.as_int()), Some(90));
+        assert_eq!(AMD_GPU_MI300X.attribute("memory_gb").and_then(|v| v.as_int()), Some(192));
+        assert!(NVIDIA_GPU_GB200.attribute("memory_gb").is_none());
+    }
+}
@@ -0,0 +1,38 @@
+//! Curated feature hierarchies for common platform parameters.
+//!
+//! Each part of the catalog is enabled by a cargo feature (`catalog` enables all of them)
+//! and registers its parameters and features at startup, so Platform.toml files and
+//! `#[assumptions]` can share the same names across projects:
+//!
+//! | cargo feature      | parameters                     |
+//! |--------------------|--------------------------------|
+//! | `catalog-simd-x86` | `cpu_simd` (SSE ... AVX-512)   |
+//! | `catalog-simd-arm` | `cpu_simd` (NEON, SVE, SVE2)   |
+//! | `catalog-cpu`      | `cpu_vendor`, `cpu_microarch`  |
+//! | `catalog-gpu`      | `acc_model`                    |
+//! | `catalog-backend`  | `acc_backend`                  |
+//!
+//! The `platformaware` feature adds the parameters of the PlatformAware.jl Platform.toml schema
+//! (see `load_platformaware`). The `model-db` feature adds a database mapping processor and
+//! accelerator model strings to catalog features, with the `cpu_cores` and `acc_memory` parameters.
+
+#[cfg(any(feature = "catalog-simd-x86", feature = "catalog-simd-arm"))]
+pub mod simd;
+#[cfg(feature = "catalog-cpu")]
+pub mod cpu;
+#[cfg(feature = "catalog-gpu")]
+pub mod gpu;
+#[cfg(feature = "catalog-backend")]
+pub mod backend;
+#[cfg(feature = "platformaware")]
+pub mod platformaware;
+#[cfg(feature = "model-db")]
+pub mod models;
+
+pub const CPU_SIMD: &str = "cpu_simd";
+pub const CPU_VENDOR: &str = "cpu_vendor";
+pub const CPU_MICROARCH: &str = "cpu_microarch";
+pub const ACC_MODEL: &str = "acc_model";
+pub const ACC_BACKEND: &str = "acc_backend";
+pub const CPU_CORES: &str = "cpu_cores";
+pub const ACC_MEMORY: &str = "acc_memory";
@@ -0,0 +1,192 @@
+//! Database mapping processor and accelerator model strings or PCI IDs to platform features.
+//!
+//! The built-in entries (`models.csv`) can be extended from a user file with the same
+//! format through `extend_model_database`. Qualifier values are resolved through the
+//! feature map when a model is looked up.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::{Feature, PlatformFeatures, PlatformParameter, declare_parameter, lookup_feature, report_resolution};
+
+/// A database line: a model name or PCI ID, and the features of that model.
+#[derive(Clone)]
+pub struct ModelEntry {
+    pub pattern: String,
+    pub features: Vec<(PlatformParameter, String)>,
+}
+
+pub static MODEL_DATABASE: Lazy<Mutex<Vec<ModelEntry>>> = Lazy::new(|| {
+    let entries = parse_model_database(include_str!("models.csv")).expect("invalid built-in model database");
+    Mutex::new(entries)
+});
+
+declare_parameter!{register_catalog_model_parameters ;
+    /// Number of CPU cores.
+    "cpu_cores" : Quantifier;
+    /// Accelerator memory, in MiB.
+    "acc_memory" : Quantifier;
+}
+
+/// Parses lines `match,parameter=value;parameter=value;...`. Blank lines and lines starting with `#` are skipped.
+pub fn parse_model_database(contents: &str) -> Result<Vec<ModelEntry>, Box<dyn Error>> {
+    let mut entries = Vec::new();
+
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        let (pattern, features) = line.split_once(',').ok_or_else(|| format!("line {}: expected `match,features`", n + 1))?;
+
+        let mut entry = ModelEntry { pattern: pattern.trim().to_string(), features: Vec::new() };
+        for assignment in features.split(';').filter(|a| !a.trim().is_empty()) {
+            let (par, value) = assignment.split_once('=').ok_or_else(|| format!("line {}: expected `parameter=value`, found `{}`", n + 1, assignment))?;
+            entry.features.push((par.trim().to_string(), value.trim().to_string()));
+        }
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Adds the entries of a user file to the database. They take precedence over the
+/// built-in entries that match a model equally well.
+pub fn extend_model_database<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn Error>> {
+    let entries = parse_model_database(&fs::read_to_string(path)?)?;
+    MODEL_DATABASE.lock().unwrap().extend(entries);
+    Ok(())
+}
+
+fn is_pci_id(s: &str) -> bool {
+    matches!(s.split_once(':'), Some((v, d)) if !v.is_empty() && !d.is_empty()
+        && v.chars().chain(d.chars()).all(|c| c.is_ascii_hexdigit()))
+}
+
+// lowercase words, without trademark marks and separators
+fn normalize(s: &str) -> String {
+    let s = s.to_lowercase().replace("(r)", " ").replace("(tm)", " ").replace(['®', '™', '-', '_', ','], " ");
+    s.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// whether pattern occurs in text as a sequence of whole words
+fn matches_words(text: &str, pattern: &str) -> bool {
+    if pattern.is_empty() { return false; }
+    text.match_indices(pattern).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + pattern.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric()) && !after.is_some_and(|c| c.is_alphanumeric())
+    })
+}
+
+/// The database entry that best matches a model string (e.g. `Intel(R) Xeon(R) Platinum 8480+`)
+/// or a PCI ID (e.g. `10de:2330`). Among matching names, the longest wins, and among those of
+/// equal length the last added (user entries over built-in ones).
+pub fn lookup_model_entry(model: &str) -> Option<ModelEntry> {
+    best_model_entry(&MODEL_DATABASE.lock().unwrap(), model)
+}
+
+fn best_model_entry(db: &[ModelEntry], model: &str) -> Option<ModelEntry> {
+    if is_pci_id(model.trim()) {
+        return db.iter().rev().find(|e| e.pattern.eq_ignore_ascii_case(model.trim())).cloned();
+    }
+
+    let text = normalize(model);
+    // max_by_key keeps the last of equal maxima
+    db.iter()
+        .filter(|e| !is_pci_id(&e.pattern) && matches_words(&text, &normalize(&e.pattern)))
+        .max_by_key(|e| normalize(&e.pattern).len())
+        .cloned()
+}
+
+/// The platform features of a model. Integer values are quantifiers; other values are
+/// looked up in the feature map, and skipped with a warning if unknown (see `set_resolution_reporter`).
+pub fn lookup_model(model: &str) -> Option<PlatformFeatures> {
+    lookup_model_entry(model).map(model_features)
+}
+
+fn model_features(entry: ModelEntry) -> PlatformFeatures {
+    let mut m = PlatformFeatures::new();
+    for (par, value) in entry.features {
+        let f: Option<Arc<dyn Feature>> = match value.parse::<i32>() {
+            Ok(n) => Some(Arc::new(n)),
+            Err(_) => lookup_feature(&value).map(|f| f as Arc<dyn Feature>),
+        };
+        match f {
+            Some(f) => { m.insert(par, f); }
+            None => report_resolution(format!("warning: unknown feature `{}` for {} of model `{}`, skipped", value, par, entry.pattern)),
+        }
+    }
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FeatureSet;
+    use crate::testing::collect_reports;
+    use crate::catalog::simd::{AVX512BW, AVX512_BF16, AVX512_FP16};
+    use super::*;
+
+    fn cores(entry: Option<ModelEntry>) -> Option<String> {
+        entry?.features.into_iter().find(|(par, _)| par == "cpu_cores").map(|(_, v)| v)
+    }
+
+    #[test]
+    fn longest_name_wins() {
+        assert_eq!(lookup_model_entry("Intel(R) Xeon(R) Gold 6148 CPU @ 2.40GHz").map(|e| e.pattern), Some("Xeon Gold 6148".to_string()));
+        assert!(lookup_model_entry("Xeon Gold 61480").is_none());
+    }
+
+    #[test]
+    fn user_entries_override_built_in_ones() {
+        // a local database, as extending MODEL_DATABASE would leak into the other tests
+        let mut db = parse_model_database(include_str!("models.csv")).unwrap();
+        db.extend(parse_model_database("Xeon Platinum 8280,cpu_cores=56\n10de:2330,acc_memory=1\n").unwrap());
+
+        assert_eq!(cores(best_model_entry(&db, "Intel Xeon Platinum 8280")), Some("56".to_string()));
+        assert_eq!(cores(lookup_model_entry("Intel Xeon Platinum 8280")), Some("28".to_string()));
+        assert_eq!(best_model_entry(&db, "10DE:2330").unwrap().features, vec![("acc_memory".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn unknown_values_are_reported() {
+        let entry = parse_model_database("Models Test Accelerator 9000,acc_model=ModelsTestUnknown;acc_memory=1024").unwrap().remove(0);
+        let mut features = PlatformFeatures::new();
+        let reported = collect_reports("ModelsTestUnknown", || features = model_features(entry));
+        assert_eq!(features.keys().collect::<Vec<_>>(), vec!["acc_memory"]);
+        assert_eq!(reported, vec!["warning: unknown feature `ModelsTestUnknown` for acc_model of model `Models Test Accelerator 9000`, skipped"]);
+    }
+
+    #[test]
+    fn built_in_values_are_features_of_their_parameter() {
+        for entry in parse_model_database(include_str!("models.csv")).unwrap() {
+            for (par, value) in entry.features {
+                if value.parse::<i32>().is_ok() { continue; }
+                let f = lookup_feature(&value).unwrap_or_else(|| panic!("{}: unknown feature {value}", entry.pattern));
+                assert_eq!(f.feature_class(), par, "{}", entry.pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn avx512_models_satisfy_the_earlier_extensions() {
+        let simd = |model: &str| FeatureSet::from(lookup_model(model).unwrap());
+        let bw = FeatureSet::new().with("cpu_simd", AVX512BW);
+        for model in ["Xeon Gold 6148", "Xeon Gold 6248", "Xeon Platinum 8380", "Xeon Platinum 8480+", "EPYC 9654"] {
+            assert!(simd(model).satisfies(&bw), "{model}");
+        }
+        assert!(!simd("EPYC 7763").satisfies(&bw));
+        assert!(simd("Xeon Platinum 8480+").satisfies(&FeatureSet::new().with("cpu_simd", AVX512_BF16)));
+        assert!(!simd("EPYC 9654").satisfies(&FeatureSet::new().with("cpu_simd", AVX512_FP16)));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(parse_model_database("# comment\n\nXeon,cpu_cores=4").is_ok());
+        assert!(parse_model_database("Xeon cpu_cores=4").is_err());
+        assert!(parse_model_database("Xeon,cpu_cores").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Parameters of the Platform.toml schema of the Julia PlatformAware package.
+//!
+//! Qualifier parameters reuse the hierarchies of the other catalog modules (PlatformAware
+//! names are translated by `load_platformaware`); accelerator types and manufacturers get
+//! their own hierarchies.
+//! Sizes are expressed in MiB; the other quantifiers keep the unit of the file.
+
+use std::sync::Arc;
+
+use crate::{Feature, ParameterDecl, ParameterKind, create_feature_hierarchy, declare_parameter_at_startup};
+
+use super::backend::ACCBackend;
+use super::cpu::{CPUMicroarchitecture, CPUVendor};
+use super::gpu::ACCModel;
+use super::simd::CPUSIMD;
+
+create_feature_hierarchy!{register_catalog_accelerator_type_root ;"accelerator_type" : None :> AcceleratorType; }
+create_feature_hierarchy!{register_catalog_accelerator_type ;"accelerator_type" : AcceleratorType :> GPU & 
+                                                                                                   TPU & 
+                                                                                                   IPU & 
+                                                                                                   FPGA & 
+                                                                                                   MIC; 
+                         }
+
+// accelerator manufacturers are not processor vendors (e.g. Xilinx), and the names of these are taken
+create_feature_hierarchy!{register_catalog_accelerator_manufacturer_root ;"accelerator_manufacturer" : None :> AcceleratorManufacturer; }
+create_feature_hierarchy!{register_catalog_accelerator_manufacturer ;"accelerator_manufacturer" : AcceleratorManufacturer :> Accelerator_NVIDIA & 
+                                                                                                                           Accelerator_AMD & 
+                                                                                                                           Accelerator_Intel & 
+                                                                                                                           Accelerator_Xilinx & 
+                                                                                                                           Accelerator_Google & 
+                                                                                                                           Accelerator_Graphcore; 
+                         }
+
+/// How a PlatformAware parameter is read from the file.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlatformAwareKind {
+    /// integer quantifier (counts, clocks, power)
+    Count,
+    /// size quantifier, converted to MiB (e.g. `"16 GB"`)
+    Size,
+    /// qualifier, looked up by name, trying each prefix in turn (e.g. `"Turing"` as `NVIDIA_GPU_Turing`)
+    Qualifier(&'static [&'static str]),
+    /// list of qualifiers, by the part of each name before the first `_` (e.g. `"CUDA_5_0"` as `CUDA`)
+    QualifierList,
+}
+
+const NO_PREFIX: &[&str] = &[""];
+const MICROARCH_PREFIXES: &[&str] = &["", "Intel_", "AMD_", "ARM_", "Apple_", "Fujitsu_"];
+const ACC_PREFIXES: &[&str] = &["", "NVIDIA_GPU_", "AMD_GPU_", "Intel_GPU_"];
+const ACC_MANUFACTURER_PREFIXES: &[&str] = &["Accelerator_"];
+
+/// The parameters of the PlatformAware schema understood by this crate.
+pub const PLATFORMAWARE_PARAMETERS: &[(&str, PlatformAwareKind)] = &[
+    ("node_count", PlatformAwareKind::Count),
+    ("node_threads_count", PlatformAwareKind::Count),
+    ("node_vcpus_count", PlatformAwareKind::Count),
+    ("node_memory_size", PlatformAwareKind::Size),
+    ("node_memory_frequency", PlatformAwareKind::Count),
+    ("node_memory_bandwidth", PlatformAwareKind::Count),
+    ("processor_count", PlatformAwareKind::Count),
+    ("processor_core_count", PlatformAwareKind::Count),
+    ("processor_core_threads_count", PlatformAwareKind::Count),
+    ("processor_core_clock", PlatformAwareKind::Count),
+    ("processor_tdp", PlatformAwareKind::Count),
+    ("processor_manufacturer", PlatformAwareKind::Qualifier(NO_PREFIX)),
+    ("processor_microarchitecture", PlatformAwareKind::Qualifier(MICROARCH_PREFIXES)),
+    ("processor_simd", PlatformAwareKind::Qualifier(NO_PREFIX)),
+    ("accelerator_count", PlatformAwareKind::Count),
+    ("accelerator_tdp", PlatformAwareKind::Count),
+    ("accelerator_processor_count", PlatformAwareKind::Count),
+    ("accelerator_memory_size", PlatformAwareKind::Size),
+    ("accelerator_type", PlatformAwareKind::Qualifier(NO_PREFIX)),
+    ("accelerator_manufacturer", PlatformAwareKind::Qualifier(ACC_MANUFACTURER_PREFIXES)),
+    ("accelerator_architecture", PlatformAwareKind::Qualifier(ACC_PREFIXES)),
+    ("accelerator", PlatformAwareKind::Qualifier(ACC_PREFIXES)),
+    ("accelerator_api", PlatformAwareKind::QualifierList),
+    ("storage_size", PlatformAwareKind::Size),
+];
+
+// root of the hierarchy used by each qualifier parameter
+fn qualifier_top(name: &str) -> Arc<dyn Feature> {
+    match name {
+        "processor_manufacturer" => Arc::new(CPUVendor),
+        "accelerator_manufacturer" => Arc::new(AcceleratorManufacturer),
+        "processor_microarchitecture" => Arc::new(CPUMicroarchitecture),
+        "processor_simd" => Arc::new(CPUSIMD),
+        "accelerator_type" => Arc::new(AcceleratorType),
+        "accelerator_api" => Arc::new(ACCBackend),
+        _ => Arc::new(ACCModel),
+    }
+}
+
+#[ctor::ctor]
+fn register_catalog_platformaware_parameters() {
+    for (name, kind) in PLATFORMAWARE_PARAMETERS {
+        let decl = match kind {
+            PlatformAwareKind::Count | PlatformAwareKind::Size => ParameterDecl::new(name, ParameterKind::Quantifier),
+            PlatformAwareKind::Qualifier(_) | PlatformAwareKind::QualifierList => ParameterDecl::new(name, ParameterKind::Qualifier).top(qualifier_top(name)),
+        };
+        declare_parameter_at_startup(decl.origin(module_path!()));
+    }
+}
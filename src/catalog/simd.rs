@@ -0,0 +1,66 @@
+//! `cpu_simd`: SIMD instruction set extensions, ordered from the oldest to the most capable.
+
+use crate::{create_feature_hierarchy, declare_parameter};
+
+create_feature_hierarchy!{register_catalog_simd ;"cpu_simd" : None :> CPUSIMD; }
+
+declare_parameter!{register_catalog_simd_parameter ;
+    /// Most capable SIMD extension of the CPU.
+    "cpu_simd" : Qualifier = CPUSIMD;
+}
+
+#[cfg(feature = "catalog-simd-x86")]
+pub use x86::*;
+
+#[cfg(feature = "catalog-simd-x86")]
+mod x86 {
+    use crate::create_feature_hierarchy;
+    use super::CPUSIMD;
+
+    // each level implies the previous ones; the AVX-512 extensions follow the generations that
+    // added them (Skylake-SP BW, Cascade Lake VNNI, Cooper Lake and Zen 4 BF16, Sapphire Rapids FP16)
+    create_feature_hierarchy!{register_catalog_simd_x86 ;"cpu_simd" : CPUSIMD :> SSE :> SSE2 :> SSE3 :> SSSE3 :> SSE4_1 :> SSE4_2 :> AVX :> AVX2 :> AVX512F :> AVX512BW :> AVX512VNNI :> AVX512_BF16 :> AVX512_FP16; }
+}
+
+#[cfg(feature = "catalog-simd-arm")]
+pub use arm::*;
+
+#[cfg(feature = "catalog-simd-arm")]
+mod arm {
+    use crate::create_feature_hierarchy;
+    use super::CPUSIMD;
+
+    create_feature_hierarchy!{register_catalog_simd_arm ;"cpu_simd" : CPUSIMD :> NEON :> SVE :> SVE2; }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Feature, FeatureObj, QualifierFeature};
+    use super::*;
+
+    fn obj<F: Feature + 'static>(f: F) -> FeatureObj { f.feature_obj() }
+
+    #[cfg(feature = "catalog-simd-x86")]
+    #[test]
+    fn x86_extensions_imply_the_earlier_ones() {
+        assert!(obj(AVX512_FP16).subtypeof(&obj(AVX512_BF16)));
+        assert!(obj(AVX512_BF16).subtypeof(&obj(AVX512VNNI)));
+        assert!(obj(AVX512VNNI).subtypeof(&obj(AVX512BW)));
+        assert!(obj(AVX512BW).subtypeof(&obj(AVX512F)));
+        assert!(obj(AVX512F).subtypeof(&obj(SSE2)));
+        assert!(obj(AVX2).satisfies(&obj(AVX)));
+        assert!(!obj(AVX2).satisfies(&obj(AVX512F)));
+        assert!(!obj(AVX512VNNI).satisfies(&obj(AVX512_BF16)));
+        assert_eq!(AVX512BW.feature_class(), "cpu_simd");
+    }
+
+    #[cfg(feature = "catalog-simd-arm")]
+    #[test]
+    fn arm_extensions_imply_the_earlier_ones() {
+        assert!(obj(SVE2).subtypeof(&obj(SVE)));
+        assert!(obj(SVE).subtypeof(&obj(NEON)));
+        assert!(obj(NEON).subtypeof(&obj(CPUSIMD)));
+        assert!(!obj(NEON).satisfies(&obj(SVE)));
+        assert_eq!(SVE.feature_class(), "cpu_simd");
+    }
+}
@@ -12,46 +12,74 @@ macro_rules! supertype {
 /// This macro allows you to define a chain of features where each feature inherits from its predecessor,
 /// ultimately linking to a base feature. It also supports defining multiple leaf features that share a common base feature.
 ///
+/// Each node may be preceded by doc comments and followed by attributes in braces, e.g.
+/// `NVIDIA_GPU_A100 { memory_gb = 80, sm = 80, desc = "..." }`. They are available at runtime
+/// through `Feature::description` and `Feature::attribute` (`desc`, or else the doc comments,
+/// is the description).
 ///
 /// # Example
 /// ```
-/// create_feature_hierarchy!{register_features_vendor ;"acc_model" : None :> ACCModel :> NVIDIA_GPU & 
-///                                                                                       AMD_GPU & 
-///                                                                                       Intel_GPU; 
+/// use configurable_features::create_feature_hierarchy;
+///
+/// create_feature_hierarchy!{register_features_root ;"acc_model" : None :> ACCModel; }
+/// create_feature_hierarchy!{register_features_vendor ;"acc_model" : ACCModel :> NVIDIA_GPU & 
+///                                                                              AMD_GPU & 
+///                                                                              Intel_GPU; 
 ///                          }                                                  
 /// create_feature_hierarchy!{register_features_arch ;"acc_model" : NVIDIA_GPU :> NVIDIA_GPU_Blackwell & 
 ///                                                                               NVIDIA_GPU_Ada & 
 ///                                                                               NVIDIA_GPU_Hopper; 
 ///                          }
-/// create_feature_hierarchy!{register_features_model ;"acc_model" : NVIDIA_GPU_Ada :> NVIDIA_GPU_A100 & 
-///                                                                                    NVIDIA_GPU_A200 & 
-///                                                                                    NVIDIA_GPU_RTX4090 & 
-///                                                                                    NVIDIA_GPU_RTX4000; 
+/// create_feature_hierarchy!{register_features_model ;"acc_model" : NVIDIA_GPU_Ada :> NVIDIA_GPU_L40S { memory_gb = 48 } & 
+///                                                                                    /// GeForce RTX 4090
+///                                                                                    NVIDIA_GPU_RTX4090 { memory_gb = 24, sm = 89 } & 
+///                                                                                    NVIDIA_GPU_RTX4000 { desc = "RTX 4000 Ada Generation" }; 
+///                          }
+///
+/// use configurable_features::Feature;
+///
+/// # fn main() {
+/// assert_eq!(NVIDIA_GPU_RTX4090.attribute("memory_gb").and_then(|v| v.as_int()), Some(24));
+/// assert_eq!(NVIDIA_GPU_RTX4090.description(), Some("GeForce RTX 4090"));
+/// assert_eq!(NVIDIA_GPU_RTX4000.description(), Some("RTX 4000 Ada Generation"));
+/// # }
 /// ```
 #[macro_export]
 macro_rules! create_feature_hierarchy {
-    (@chain { $class_name:literal } $name:ident :> $next:ident $( :> $rest:ident )*) => {
+    (@node { $class_name:literal } $sup:ident ; $(#[doc = $doc:literal])* $name:ident $({ $($key:ident = $value:literal),* $(,)? })?) => {
+        $(#[doc = $doc])*
         #[derive(Clone)]
         #[allow(non_camel_case_types)]
-        pub struct $next;
-        
-        impl configurable_features::Feature for $next {
+        pub struct $name;
+
+        impl configurable_features::Feature for $name {
           //  fn feature_type(self:&Self) -> configurable_features::FeatureKind { configurable_features::FeatureKind::Qualifier }
-            fn feature_obj(self:&Self) -> configurable_features::FeatureObj { configurable_features::FeatureObj::Qualifier(std::sync::Arc::new(self.clone()) ) }
-            fn string(&self) -> String { stringify!($next).to_string() }
-            fn supertype(&self) -> Option<Box<dyn configurable_features::Feature>> { configurable_features::supertype!($name) }
+            fn feature_obj(self:&Self) -> configurable_features::FeatureObj { configurable_features::FeatureObj::Qualifier(std::sync::Arc::new(self.clone()) as std::sync::Arc<dyn configurable_features::QualifierFeature>) }
+            fn string(&self) -> String { stringify!($name).to_string() }
+            fn supertype(&self) -> Option<Box<dyn configurable_features::Feature>> { configurable_features::supertype!($sup) }
+            fn description(&self) -> Option<&'static str> {
+                if let Some(configurable_features::FeatureAttribute::Str(s)) = self.attribute("desc") { return Some(s); }
+                let doc: &'static str = concat!($($doc, "\n",)* "");
+                if doc.trim().is_empty() { None } else { Some(doc.trim()) }
+            }
+            fn attributes(&self) -> Vec<(&'static str, configurable_features::FeatureAttribute)> {
+                vec![ $($( (stringify!($key), configurable_features::FeatureAttribute::from($value)) ),*)? ]
+            }
         }
 
-        impl configurable_features::QualifierFeature for $next {
+        impl configurable_features::QualifierFeature for $name {
             fn feature_class(&self) -> configurable_features::PlatformParameter { $class_name.to_string() }
         }
+    };
 
-        create_feature_hierarchy!(@chain { $class_name } $next $( :> $rest )*);
+    (@chain { $class_name:literal } $name:ident :> $(#[doc = $doc:literal])* $next:ident $({ $($key:ident = $value:literal),* $(,)? })? $(:> $($rest:tt)*)?) => {
+        create_feature_hierarchy!(@node { $class_name } $name ; $(#[doc = $doc])* $next $({ $($key = $value),* })?);
+        create_feature_hierarchy!(@chain { $class_name } $next $(:> $($rest)*)?);
     };
 
      (@chain { $class_name:literal } $base:ident) => {  };
 
-    ( $tag:ident ; $class_name:literal : $head:ident $( :> $tail:ident )+ ; ) => {
+    ( $tag:ident ; $class_name:literal : $head:ident $( :> $(#[doc = $doc:literal])* $tail:ident $({ $($key:ident = $value:literal),* $(,)? })? )+ ; ) => {
         paste::paste! {
             #[allow(non_snake_case)]
             #[ctor::ctor]
@@ -61,27 +89,14 @@ macro_rules! create_feature_hierarchy {
                 )*
             }
         }
-        create_feature_hierarchy!(@chain { $class_name } $head $( :> $tail )*);
+        create_feature_hierarchy!(@chain { $class_name } $head $( :> $(#[doc = $doc])* $tail $({ $($key = $value),* })? )*);
     };
 
 
 
-    ( $tag:ident ; $class_name:literal : $base:ident :> $($leaf:ident)&+; ) => {
+    ( $tag:ident ; $class_name:literal : $base:ident :> $( $(#[doc = $doc:literal])* $leaf:ident $({ $($key:ident = $value:literal),* $(,)? })? )&+; ) => {
         $(
-            #[derive(Clone)]
-            #[allow(non_camel_case_types)]
-            pub struct $leaf;
-
-            impl configurable_features::Feature for $leaf {
-               // fn feature_type(self:&Self) -> configurable_features::FeatureKind { configurable_features::FeatureKind::Qualifier }
-                fn feature_obj(self:&Self) -> configurable_features::FeatureObj { configurable_features::FeatureObj::Qualifier(std::sync::Arc::new(self.clone()) as std::sync::Arc<dyn configurable_features::QualifierFeature>) }
-                fn string(&self) -> String { stringify!($leaf).to_string() }
-                fn supertype(&self) -> Option<Box<dyn configurable_features::Feature>> { Some(Box::new($base)) }
-            }
-
-            impl configurable_features::QualifierFeature for $leaf {
-                fn feature_class(&self) -> configurable_features::PlatformParameter { $class_name.to_string() }
-            }
+            create_feature_hierarchy!(@node { $class_name } $base ; $(#[doc = $doc])* $leaf $({ $($key = $value),* })?);
         )*
         paste::paste! {
             #[allow(non_snake_case)]
@@ -0,0 +1,47 @@
+/// Macro that declares typed platform parameters.
+///
+/// Each entry gives the parameter name, its kind (`Qualifier`, `Quantifier`, `Flag` or `Version`,
+/// see `ParameterKind`), its top and, optionally, the value assumed when the platform does not
+/// declare it. The top may be omitted for all kinds but `Qualifier`. Doc comments are kept in the
+/// declaration. A parameter declared twice, in this crate or any other, or with a top other than
+/// the one it was registered with, keeps its first registration: the error is returned by
+/// `parameter_errors` and reported as a warning by the first resolution (see `set_resolution_reporter`).
+///
+/// # Example
+/// ```
+/// use configurable_features::{AtLeast, create_feature_hierarchy, declare_parameter};
+///
+/// create_feature_hierarchy!{register_features_root ;"interconnect" : None :> Interconnect; }
+/// create_feature_hierarchy!{register_features_kinds ;"interconnect" : Interconnect :> Ethernet &
+///                                                                                     InfiniBand;
+///                          }
+///
+/// declare_parameter!{register_parameters ;
+///     /// Network between the nodes.
+///     "interconnect" : Qualifier = Interconnect;
+///     /// Number of nodes of the job.
+///     "job_nodes" : Quantifier, default = AtLeast { val: 1 };
+///     "has_gpudirect" : Flag;
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! declare_parameter {
+    (@top) => { None };
+    (@top $top:expr) => { Some(std::sync::Arc::new($top) as std::sync::Arc<dyn configurable_features::Feature>) };
+
+    ( $tag:ident ; $( $(#[doc = $doc:literal])* $name:literal : $kind:ident $(= $top:expr)? $(, default = $default:expr)? );+ $(;)? ) => {
+        #[allow(non_snake_case)]
+        #[ctor::ctor]
+        fn $tag() {
+            $(
+                let mut decl = configurable_features::ParameterDecl::new($name, configurable_features::ParameterKind::$kind)
+                    .doc(concat!($($doc, "\n",)* "").trim())
+                    .origin(module_path!());
+                decl.top = configurable_features::declare_parameter!(@top $($top)?);
+                decl.default = configurable_features::declare_parameter!(@top $($default)?);
+                configurable_features::declare_parameter_at_startup(decl);
+            )+
+        }
+    };
+}
@@ -0,0 +1,50 @@
+
+/// Macro that registers alternative names for features of a hierarchy.
+///
+/// Each alias becomes a constant of the canonical feature type, so it can be written in
+/// `#[assumptions(...)]`, and is registered with `insert_alias`, so Platform.toml files may
+/// use it as well. Aliases marked `[deprecated]` produce a compile-time warning where the
+/// constant is used and a runtime warning when a platform description uses the name.
+///
+/// # Example
+/// ```
+/// use configurable_features::{create_feature_hierarchy, feature_alias};
+///
+/// create_feature_hierarchy!{register_features_root ;"acc_model" : None :> ACCModel; }
+/// create_feature_hierarchy!{register_features_vendor ;"acc_model" : ACCModel :> NVIDIA_GPU; }
+/// create_feature_hierarchy!{register_features_arch ;"acc_model" : NVIDIA_GPU :> NVIDIA_GPU_Ampere; }
+///
+/// feature_alias!{register_aliases_nvidia ; Ampere => NVIDIA_GPU_Ampere;
+///                                          sm_80 => NVIDIA_GPU_Ampere [deprecated];
+///               }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! feature_alias {
+    (@const $alias:ident $canonical:ident deprecated) => {
+        #[allow(non_upper_case_globals)]
+        #[doc = concat!("Deprecated alias of `", stringify!($canonical), "`.")]
+        #[deprecated = concat!("deprecated feature alias, use `", stringify!($canonical), "` instead")]
+        pub const $alias: $canonical = $canonical;
+    };
+    (@const $alias:ident $canonical:ident) => {
+        #[allow(non_upper_case_globals)]
+        #[doc = concat!("Alias of `", stringify!($canonical), "`.")]
+        pub const $alias: $canonical = $canonical;
+    };
+
+    (@deprecated deprecated) => { true };
+    (@deprecated) => { false };
+
+    ( $tag:ident ; $( $alias:ident => $canonical:ident $( [ $dep:ident ] )? );+ $(;)? ) => {
+        $( configurable_features::feature_alias!(@const $alias $canonical $($dep)?); )+
+
+        #[allow(non_snake_case)]
+        #[ctor::ctor]
+        fn $tag() {
+            $(
+                configurable_features::insert_alias(stringify!($alias), stringify!($canonical), configurable_features::feature_alias!(@deprecated $($dep)?));
+            )+
+        }
+    };
+}
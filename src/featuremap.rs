@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Mutex};
 
 use once_cell::sync::Lazy;
 
-use crate::{QualifierFeature};
+use crate::{Feature, FeatureObj, QualifierFeature};
 
 use std::sync::Arc;
 
@@ -12,30 +12,149 @@ pub static FEATURE_MAP: Lazy<Mutex<HashMap<String, Arc<dyn QualifierFeature>>>>
     Mutex::new(map)
 });
 
+/// Alternative name of a registered feature.
+pub struct FeatureAlias {
+    pub canonical: String,
+    pub deprecated: bool,
+    warned: bool,
+}
+
+pub static FEATURE_ALIASES: Lazy<Mutex<HashMap<String, FeatureAlias>>> = Lazy::new(|| {
+    let map: HashMap<String, FeatureAlias> = HashMap::new();
+    Mutex::new(map)
+});
+
 pub fn insert_feature(fvalue: Arc<dyn QualifierFeature>) {
     let mut dict = FEATURE_MAP.lock().unwrap();
-    // println!("insert_feature({})",fvalue.string());
     dict.insert(fvalue.string(), fvalue);
+}
 
-    /*for (key, feature) in &dict.clone() {
-        println!("--- {}: {}", key, feature.string());
-    }*/
+/// Registers `alias` as another name of the feature named `canonical`, accepted by `lookup_feature`
+/// (thus in Platform.toml files) and by string values in assumptions. Looking up a deprecated alias
+/// prints a warning naming the replacement, once.
+pub fn insert_alias(alias: &str, canonical: &str, deprecated: bool) {
+    let mut aliases = FEATURE_ALIASES.lock().unwrap();
+    aliases.insert(alias.to_string(), FeatureAlias { canonical: canonical.to_string(), deprecated, warned: false });
+}
 
+/// Resolves an alias to the canonical feature name. Other names are returned unchanged.
+pub fn canonical_feature_name(fname: &str) -> String {
+    let (canonical, warning) = resolve_alias(&mut FEATURE_ALIASES.lock().unwrap(), fname);
+    if let Some(warning) = warning { crate::report_resolution(warning); }
+    canonical
 }
 
-pub fn lookup_feature(fname: &'static str) -> Option<Arc<dyn QualifierFeature>> {
-    let dict = FEATURE_MAP.lock().unwrap();
-/*     println!("LOOKUP FEATURE {fname}");
+// the canonical name, and the deprecation warning to report (once the locks are released)
+fn resolve_alias(aliases: &mut HashMap<String, FeatureAlias>, fname: &str) -> (String, Option<String>) {
+    match aliases.get_mut(fname) {
+        Some(alias) => {
+            let mut warning = None;
+            if alias.deprecated && !alias.warned {
+                warning = Some(format!("warning: feature name `{}` is deprecated, use `{}` instead", fname, alias.canonical));
+                alias.warned = true;
+            }
+            (alias.canonical.clone(), warning)
+        }
+        None => (fname.to_string(), None),
+    }
+}
+
+/// The aliases registered for a canonical feature name.
+pub fn feature_aliases(canonical: &str) -> Vec<String> {
+    let aliases = FEATURE_ALIASES.lock().unwrap();
+    let mut names: Vec<String> = aliases.iter()
+        .filter(|(_, a)| a.canonical == canonical)
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
 
-     for (key, feature) in &dict.clone() {
-        println!("+++ {}: {}", key, feature.string());
-    }*/
+pub fn lookup_feature(fname: &str) -> Option<Arc<dyn QualifierFeature>> {
+    // one lookup under the feature map lock (taken before the aliases lock)
+    let (found, warning) = {
+        let dict = FEATURE_MAP.lock().unwrap();
+        // canonical names take precedence over aliases
+        match dict.get(fname) {
+            Some(v) => (Some(v.clone()), None),
+            None => {
+                let (canonical, warning) = resolve_alias(&mut FEATURE_ALIASES.lock().unwrap(), fname);
+                (dict.get(&canonical).cloned(), warning)
+            }
+        }
+    };
+    if let Some(warning) = warning { crate::report_resolution(warning); }
+    found
+}
 
-    match dict.get(fname) {
-        Some(v) => Some(v.clone()),
-        None => None,
+/// The feature named `fname` (or one of its aliases), for assumptions written with a string value
+/// such as `acc_model = "sm_80"`. An unknown name prints a warning and yields a feature no
+/// platform value is a subtype of, so the assumption is incompatible (or, negated, always met).
+pub fn named_feature(fname: &str) -> Arc<dyn Feature> {
+    match lookup_feature(fname) {
+        Some(f) => f,
+        None => {
+            crate::report_resolution(format!("warning: unknown feature `{}` in assumptions, no platform satisfies it", fname));
+            Arc::new(UnknownFeature(fname.to_string()))
+        }
     }
+}
 
-//    dict.get(fname).unwrap().clone()
+// value of an assumption naming an unregistered feature, outside of every hierarchy
+struct UnknownFeature(String);
+
+impl Feature for UnknownFeature {
+    fn feature_obj(&self) -> FeatureObj { FeatureObj::Qualifier(Arc::new(UnknownFeature(self.0.clone()))) }
+    fn string(&self) -> String { self.0.clone() }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::{Negated, feature_alias};
+    use crate::testing::{TestAcc, TestGpu, collect_reports};
+    use super::*;
+
+    feature_alias!{register_featuremap_test_aliases ; AliasTestGraphics => TestGpu;
+                                                      alias_test_gfx => TestGpu [deprecated];
+                  }
+
+    #[test]
+    #[allow(deprecated)]
+    fn aliases_resolve_to_canonical_features() {
+        assert_eq!(AliasTestGraphics.string(), "TestGpu");
+        assert_eq!(alias_test_gfx.string(), "TestGpu");
+        assert_eq!(canonical_feature_name("AliasTestGraphics"), "TestGpu");
+        assert_eq!(canonical_feature_name("alias_test_gfx"), "TestGpu");
+        assert_eq!(canonical_feature_name("AliasTestUnknown"), "AliasTestUnknown");
+        assert_eq!(lookup_feature("alias_test_gfx").map(|f| f.string()), Some("TestGpu".to_string()));
+        assert_eq!(feature_aliases("TestGpu"), vec!["AliasTestGraphics", "alias_test_gfx"]);
+    }
+
+    #[test]
+    fn deprecated_aliases_are_reported_once() {
+        insert_alias("featuremap_test_old_gpu", "TestGpu", true);
+        let reported = collect_reports("featuremap_test_old_gpu", || {
+            assert_eq!(lookup_feature("featuremap_test_old_gpu").map(|f| f.string()), Some("TestGpu".to_string()));
+            assert_eq!(canonical_feature_name("featuremap_test_old_gpu"), "TestGpu");
+        });
+        assert_eq!(reported, vec!["warning: feature name `featuremap_test_old_gpu` is deprecated, use `TestGpu` instead"]);
+    }
+
+    #[test]
+    fn canonical_names_take_precedence_over_aliases() {
+        insert_alias("TestFpga", "TestAcc", false);
+        assert_eq!(lookup_feature("TestFpga").map(|f| f.string()), Some("TestFpga".to_string()));
+    }
+
+    #[test]
+    fn unknown_named_features_are_never_satisfied() {
+        assert_eq!(named_feature("AliasTestGraphics").string(), "TestGpu");
+
+        let unknown = named_feature("AliasTestMissing");
+        assert_eq!(unknown.string(), "AliasTestMissing");
+        for platform in [TestAcc.feature_obj(), TestGpu.feature_obj()] {
+            assert!(!platform.satisfies(&unknown.feature_obj()));
+            assert!(platform.satisfies(&Negated(unknown.clone()).feature_obj()));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::{Feature, FeatureObj, PlatformFeatures, PlatformParameter, current_platform};
+
+use super::{FeatureHierarchy, feature_classes, feature_hierarchy};
+
+/// Selects what an exported feature lattice contains and which nodes are highlighted.
+#[derive(Default)]
+pub struct ExportOptions {
+    parameters: Vec<PlatformParameter>,
+    highlight_platform: bool,
+    assumptions: Vec<(String, PlatformFeatures)>,
+}
+
+impl ExportOptions {
+
+    pub fn new() -> Self { Self::default() }
+
+    /// Restricts the export to the given parameter. By default, every feature class is exported.
+    pub fn parameter(mut self, parameter: &str) -> Self {
+        self.parameters.push(parameter.to_string());
+        self
+    }
+
+    /// Highlights the features of the current platform (`current_platform`).
+    pub fn highlight_current_platform(mut self) -> Self {
+        self.highlight_platform = true;
+        self
+    }
+
+    /// Marks the qualifiers assumed by a kernel variant, annotated with `label`.
+    pub fn highlight_assumptions(mut self, label: &str, assumptions: &PlatformFeatures) -> Self {
+        self.assumptions.push((label.to_string(), assumptions.clone()));
+        self
+    }
+}
+
+// names of the qualifier features held by a feature value (quantifiers have no node in the lattice)
+fn qualifier_names(f: &Arc<dyn Feature>) -> Vec<String> {
+    match f.feature_obj() {
+        FeatureObj::Qualifier(q) => vec![q.string()],
+        FeatureObj::QualifierVec(v) => v.iter().map(|q| q.string()).collect(),
+        FeatureObj::Disjunction(fs) | FeatureObj::Conjunction(fs) => fs.iter().flat_map(qualifier_names).collect(),
+        FeatureObj::Quantifier(_) | FeatureObj::Negation(_) => vec![],
+    }
+}
+
+struct Lattice {
+    hierarchies: Vec<FeatureHierarchy>,
+    platform: BTreeSet<String>,
+    assumed: BTreeMap<String, Vec<String>>,
+}
+
+fn collect(options: &ExportOptions) -> Lattice {
+    let parameters = if options.parameters.is_empty() { feature_classes() } else { options.parameters.clone() };
+
+    let mut platform = BTreeSet::new();
+    if options.highlight_platform {
+        for f in current_platform().values() {
+            platform.extend(qualifier_names(f));
+        }
+    }
+
+    let mut assumed: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (label, assumptions) in &options.assumptions {
+        for f in assumptions.values() {
+            for name in qualifier_names(f) {
+                assumed.entry(name).or_default().push(label.clone());
+            }
+        }
+    }
+
+    let hierarchies: Vec<FeatureHierarchy> = parameters.iter().map(|p| feature_hierarchy(p)).collect();
+
+    // highlighted features outside the exported hierarchies are not drawn
+    platform.retain(|name| hierarchies.iter().any(|h| h.contains(name)));
+    assumed.retain(|name, _| hierarchies.iter().any(|h| h.contains(name)));
+
+    Lattice { hierarchies, platform, assumed }
+}
+
+fn dot_escape(s: &str) -> String { s.replace('\\', "\\\\").replace('"', "\\\"") }
+
+// Mermaid labels take entity codes instead of backslash escapes
+fn mermaid_escape(s: &str) -> String {
+    s.replace('#', "#35;").replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
+
+fn mermaid_id(s: &str) -> String {
+    s.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect()
+}
+
+/// Renders the registered feature hierarchies as a Graphviz DOT digraph.
+///
+/// Edges go from each feature to its supertype. Each parameter is drawn as a cluster.
+pub fn export_dot(options: &ExportOptions) -> String {
+    let lattice = collect(options);
+    let mut s = String::new();
+
+    writeln!(s, "digraph features {{").unwrap();
+    writeln!(s, "    rankdir=BT;").unwrap();
+    writeln!(s, "    node [shape=box];").unwrap();
+
+    for (i, h) in lattice.hierarchies.iter().enumerate() {
+        writeln!(s, "    subgraph cluster_{i} {{").unwrap();
+        writeln!(s, "        label=\"{}\";", dot_escape(h.parameter())).unwrap();
+
+        for name in h.names() {
+            let mut attrs = Vec::new();
+            if lattice.platform.contains(&name) {
+                attrs.push("style=filled".to_string());
+                attrs.push("fillcolor=lightblue".to_string());
+            }
+            if let Some(desc) = h.feature(&name).and_then(|f| f.description()) {
+                attrs.push(format!("tooltip=\"{}\"", dot_escape(desc)));
+            }
+            if let Some(labels) = lattice.assumed.get(&name) {
+                attrs.push("peripheries=2".to_string());
+                attrs.push(format!("xlabel=\"{}\"", dot_escape(&labels.join(", "))));
+            }
+            if attrs.is_empty() {
+                writeln!(s, "        \"{}\";", dot_escape(&name)).unwrap();
+            } else {
+                writeln!(s, "        \"{}\" [{}];", dot_escape(&name), attrs.join(", ")).unwrap();
+            }
+        }
+
+        for name in h.names() {
+            if let Some(parent) = h.parent(&name) {
+                writeln!(s, "        \"{}\" -> \"{}\";", dot_escape(&name), dot_escape(&parent)).unwrap();
+            }
+        }
+
+        writeln!(s, "    }}").unwrap();
+    }
+
+    writeln!(s, "}}").unwrap();
+    s
+}
+
+/// Renders the registered feature hierarchies as a Mermaid flowchart.
+///
+/// Edges go from each feature to its supertype. Each parameter is drawn as a subgraph.
+pub fn export_mermaid(options: &ExportOptions) -> String {
+    let lattice = collect(options);
+    let mut s = String::new();
+
+    writeln!(s, "flowchart BT").unwrap();
+
+    for h in &lattice.hierarchies {
+        writeln!(s, "    subgraph {}[\"{}\"]", mermaid_id(&format!("param_{}", h.parameter())), mermaid_escape(h.parameter())).unwrap();
+
+        for name in h.names() {
+            match lattice.assumed.get(&name) {
+                Some(labels) => writeln!(s, "        {}[\"{}<br/><i>{}</i>\"]", mermaid_id(&name), mermaid_escape(&name), mermaid_escape(&labels.join(", "))).unwrap(),
+                None => writeln!(s, "        {}[\"{}\"]", mermaid_id(&name), mermaid_escape(&name)).unwrap(),
+            }
+        }
+
+        for name in h.names() {
+            if let Some(parent) = h.parent(&name) {
+                writeln!(s, "        {} --> {}", mermaid_id(&name), mermaid_id(&parent)).unwrap();
+            }
+        }
+
+        writeln!(s, "    end").unwrap();
+    }
+
+    if !lattice.platform.is_empty() {
+        writeln!(s, "    classDef platform fill:#add8e6").unwrap();
+        let ids: Vec<String> = lattice.platform.iter().map(|n| mermaid_id(n)).collect();
+        writeln!(s, "    class {} platform", ids.join(",")).unwrap();
+    }
+
+    if !lattice.assumed.is_empty() {
+        writeln!(s, "    classDef assumed stroke-width:3px,stroke-dasharray:4").unwrap();
+        let ids: Vec<String> = lattice.assumed.keys().map(|n| mermaid_id(n)).collect();
+        writeln!(s, "    class {} assumed", ids.join(",")).unwrap();
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FeatureSet, with_platform};
+    use crate::testing::{TestA100, TestAvx, TestSimd, node};
+    use super::*;
+
+    fn highlighted(export: fn(&ExportOptions) -> String) -> String {
+        let options = ExportOptions::new().parameter("test_simd").highlight_current_platform()
+            .highlight_assumptions("generic", &FeatureSet::new().with("test_simd", TestSimd))
+            .highlight_assumptions("avx", &FeatureSet::new().with("test_simd", TestAvx));
+        with_platform(node(), || export(&options))
+    }
+
+    #[test]
+    fn dot_golden_output() {
+        assert_eq!(highlighted(export_dot), r#"digraph features {
+    rankdir=BT;
+    node [shape=box];
+    subgraph cluster_0 {
+        label="test_simd";
+        "TestAvx" [style=filled, fillcolor=lightblue, tooltip="Advanced Vector Extensions", peripheries=2, xlabel="avx"];
+        "TestSimd" [peripheries=2, xlabel="generic"];
+        "TestAvx" -> "TestSimd";
+    }
+}
+"#);
+    }
+
+    #[test]
+    fn mermaid_golden_output() {
+        assert_eq!(highlighted(export_mermaid), r#"flowchart BT
+    subgraph param_test_simd["test_simd"]
+        TestAvx["TestAvx<br/><i>avx</i>"]
+        TestSimd["TestSimd<br/><i>generic</i>"]
+        TestAvx --> TestSimd
+    end
+    classDef platform fill:#add8e6
+    class TestAvx platform
+    classDef assumed stroke-width:3px,stroke-dasharray:4
+    class TestAvx,TestSimd assumed
+"#);
+    }
+
+    #[test]
+    fn exports_draw_each_feature_with_an_edge_to_its_supertype() {
+        let options = ExportOptions::new().parameter("test_acc");
+
+        let dot = export_dot(&options);
+        assert!(dot.starts_with("digraph features {") && dot.contains("label=\"test_acc\";"));
+        assert!(dot.contains("        \"TestA100\" -> \"TestAmpere\";"));
+        assert!(dot.contains("        \"TestAcc\";") && !dot.contains("\"TestAcc\" ->"));
+        assert!(!dot.contains("TestAvx"));
+
+        let mermaid = export_mermaid(&options);
+        assert!(mermaid.starts_with("flowchart BT") && mermaid.contains("    subgraph param_test_acc[\"test_acc\"]"));
+        assert!(mermaid.contains("        TestA100 --> TestAmpere"));
+        assert!(!mermaid.contains("TestAvx") && !mermaid.contains("classDef"));
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq!(dot_escape("say \"hi\" \\"), "say \\\"hi\\\" \\\\");
+        assert_eq!(mermaid_escape("a \"b\" <c> #d"), "a #quot;b#quot; #lt;c#gt; #35;d");
+
+        let options = ExportOptions::new().parameter("test_acc")
+            .highlight_assumptions("kernel \"fast\"", &FeatureSet::new().with("test_acc", TestA100).with("test_simd", TestAvx));
+        assert!(export_dot(&options).contains("xlabel=\"kernel \\\"fast\\\"\""));
+        assert!(export_mermaid(&options).contains("TestA100[\"TestA100<br/><i>kernel #quot;fast#quot;</i>\"]"));
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use crate::{PlatformParameter, QualifierFeature, featuremap::FEATURE_MAP};
+
+mod export;
+
+pub use export::*;
+
+/// Snapshot of the qualifier features registered for one platform parameter,
+/// arranged as a forest that follows the supertype relation.
+///
+/// Nodes are identified by `Feature::string()`. Supertypes that are not registered
+/// in the feature map themselves still appear as nodes, so the chain up to the
+/// root is always complete.
+pub struct FeatureHierarchy {
+    parameter: PlatformParameter,
+    features: HashMap<String, Arc<dyn QualifierFeature>>,
+    parents: HashMap<String, Option<String>>,
+    children: HashMap<String, Vec<String>>,
+}
+
+/// Builds the hierarchy of the features whose `feature_class()` is `parameter`.
+pub fn feature_hierarchy(parameter: &str) -> FeatureHierarchy {
+    let dict = FEATURE_MAP.lock().unwrap();
+
+    let mut hierarchy = FeatureHierarchy {
+        parameter: parameter.to_string(),
+        features: HashMap::new(),
+        parents: HashMap::new(),
+        children: HashMap::new(),
+    };
+
+    for (name, feature) in dict.iter() {
+        if feature.feature_class() != parameter { continue; }
+        hierarchy.features.insert(name.clone(), feature.clone());
+
+        // walk up the supertype chain, stopping at the first node already seen
+        let mut current = name.clone();
+        let mut s = feature.supertype();
+        loop {
+            if hierarchy.parents.contains_key(&current) { break; }
+            let parent = s.as_ref().map(|sup| sup.string());
+            hierarchy.parents.insert(current.clone(), parent.clone());
+            match parent {
+                Some(p) => {
+                    hierarchy.children.entry(p.clone()).or_default().push(current);
+                    s = s.and_then(|sup| sup.supertype());
+                    current = p;
+                }
+                None => break,
+            }
+        }
+    }
+
+    for c in hierarchy.children.values_mut() {
+        c.sort();
+    }
+
+    hierarchy
+}
+
+/// Lists the distinct feature classes (platform parameters) of the registered qualifier features.
+pub fn feature_classes() -> Vec<PlatformParameter> {
+    let dict = FEATURE_MAP.lock().unwrap();
+    let classes: BTreeSet<PlatformParameter> = dict.values().map(|f| f.feature_class()).collect();
+    classes.into_iter().collect()
+}
+
+impl FeatureHierarchy {
+
+    pub fn parameter(&self) -> &PlatformParameter { &self.parameter }
+
+    /// All nodes of the hierarchy, in lexicographic order.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.parents.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn contains(&self, name: &str) -> bool { self.parents.contains_key(name) }
+
+    /// The registered feature object of a node, if the node was registered in the feature map.
+    pub fn feature(&self, name: &str) -> Option<Arc<dyn QualifierFeature>> {
+        self.features.get(name).cloned()
+    }
+
+    /// Nodes without a supertype.
+    pub fn roots(&self) -> Vec<String> {
+        let mut roots: Vec<String> = self.parents.iter()
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(name, _)| name.clone())
+            .collect();
+        roots.sort();
+        roots
+    }
+
+    pub fn parent(&self, name: &str) -> Option<String> {
+        self.parents.get(name).cloned().flatten()
+    }
+
+    /// Direct subtypes of a node.
+    pub fn children(&self, name: &str) -> Vec<String> {
+        self.children.get(name).cloned().unwrap_or_default()
+    }
+
+    /// All subtypes of a node, in depth-first order.
+    pub fn descendants(&self, name: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut stack: Vec<String> = self.children(name).into_iter().rev().collect();
+        while let Some(n) = stack.pop() {
+            stack.extend(self.children(&n).into_iter().rev());
+            result.push(n);
+        }
+        result
+    }
+
+    /// Supertype chain of a node, from its direct supertype up to the root.
+    pub fn ancestors(&self, name: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut current = self.parent(name);
+        while let Some(p) = current {
+            if result.contains(&p) { break; }
+            current = self.parent(&p);
+            result.push(p);
+        }
+        result
+    }
+
+    /// Distance of a node from its root (roots have depth 0).
+    pub fn depth(&self, name: &str) -> usize { self.ancestors(name).len() }
+
+    pub fn is_ancestor_of(&self, ancestor: &str, name: &str) -> bool {
+        self.ancestors(name).iter().any(|a| a == ancestor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(v: &[&str]) -> Vec<String> { v.iter().map(|s| s.to_string()).collect() }
+
+    #[test]
+    fn hierarchies_follow_the_supertype_chain() {
+        let h = feature_hierarchy("test_acc");
+        assert_eq!(h.parameter(), "test_acc");
+        assert_eq!(h.roots(), names(&["TestAcc"]));
+        assert_eq!(h.names(), names(&["TestA100", "TestA30", "TestAcc", "TestAmpere", "TestFpga", "TestGpu", "TestHopper"]));
+        assert_eq!(h.parent("TestAmpere").as_deref(), Some("TestGpu"));
+        assert_eq!(h.parent("TestAcc"), None);
+        assert_eq!(h.children("TestGpu"), names(&["TestAmpere", "TestHopper"]));
+        assert!(h.children("TestA30").is_empty());
+        assert_eq!(h.feature("TestA100").map(|f| f.string()), Some("TestA100".to_string()));
+        assert!(!h.contains("TestAvx"));
+        assert!(feature_classes().contains(&"test_acc".to_string()));
+    }
+
+    #[test]
+    fn ancestors_and_descendants() {
+        let h = feature_hierarchy("test_acc");
+        assert_eq!(h.ancestors("TestA100"), names(&["TestAmpere", "TestGpu", "TestAcc"]));
+        assert!(h.ancestors("TestAcc").is_empty());
+        assert_eq!(h.descendants("TestGpu"), names(&["TestAmpere", "TestA100", "TestA30", "TestHopper"]));
+        assert_eq!(h.descendants("TestAcc").len(), 6);
+        assert!(h.descendants("TestFpga").is_empty());
+        assert_eq!((h.depth("TestAcc"), h.depth("TestA30")), (0, 3));
+        assert!(h.is_ancestor_of("TestGpu", "TestA30"));
+        assert!(!h.is_ancestor_of("TestFpga", "TestA30") && !h.is_ancestor_of("TestA30", "TestA30"));
+    }
+}
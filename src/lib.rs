@@ -2,12 +2,22 @@ mod base;
 mod platformfile;
 mod featuremap;
 mod resolve;
+mod hierarchy;
+#[cfg(test)]
+mod testing;
 
 pub mod create_feature_hierarchy; 
+pub mod feature_alias;
+pub mod declare_parameter;
+pub mod catalog;
+
+// lets the exported macros, which name `configurable_features::...`, be used inside this crate
+extern crate self as configurable_features;
 
 pub use base::*;
 pub use platformfile::*;
 pub use resolve::*;
-pub use featuremap::{insert_feature, lookup_feature};
+pub use hierarchy::*;
+pub use featuremap::{FeatureAlias, canonical_feature_name, feature_aliases, insert_alias, insert_feature, lookup_feature, named_feature};
 pub use configurable_macros::configurable;
-pub use configurable_internal::__internal_configurable;
+pub use configurable_internal::{__internal_configurable, __internal_configurable_with};
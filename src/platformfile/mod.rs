@@ -5,9 +5,18 @@ use std::{env, fs};
 use std::error::Error;
 
 use once_cell::sync::Lazy;
-use serde::de::DeserializeOwned;
+use serde_core::de::DeserializeOwned;
 use std::collections::HashMap;
 
+#[cfg(feature = "platformaware")]
+mod platformaware;
+
+#[cfg(feature = "platformaware")]
+pub use platformaware::*;
+
+mod pin;
+
+pub use pin::*;
 
 pub static CURRENT_FEATURES: Lazy<Mutex<PlatformFeatures>> = Lazy::new(|| {
     let m = HashMap::new(); // readplatformfeatures();
@@ -40,19 +49,28 @@ pub fn readplatform<P:DeserializeOwned>() -> Result<P,Box<dyn Error>> {
 
 
 
-use crate::{lookup_feature, PlatformFeatures, PlatformParameter};
+use crate::{evaluate_derived_parameters, lookup_feature, PlatformFeatures, PlatformParameter};
 
 use super::Feature;
 
 
 pub fn add_qualifier(m: &mut HashMap<PlatformParameter, Arc<dyn Feature>>, par:PlatformParameter, v:String) { 
-    let f = lookup_feature(Box::leak(v.into_boxed_str()));
-    match f {
-        Some(f) => { m.insert(par, f); },
-        None => {},
-    }
+    let f = lookup_feature(&v);
+    if let Some(f) = f { m.insert(par, f); }
 }
 
 pub fn add_quantifier(m: &mut HashMap<PlatformParameter, Arc<dyn Feature>>, par:PlatformParameter, v:i32) { 
     m.insert(par, Arc::new(v));
-}
\ No newline at end of file
+}
+/// Stores the derived parameters (see `insert_derived_parameter`) in `CURRENT_FEATURES`.
+/// Call it after loading or probing the platform features. Resolution evaluates them anyway.
+pub fn update_derived_parameters() {
+    // the closures run on a copy, as they may read CURRENT_FEATURES themselves
+    let mut m = CURRENT_FEATURES.lock().unwrap().clone();
+    evaluate_derived_parameters(&mut m);
+
+    let mut current = CURRENT_FEATURES.lock().unwrap();
+    for (par, v) in m {
+        current.entry(par).or_insert(v);
+    }
+}
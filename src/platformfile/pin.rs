@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use configurable_internal::{KernelFunction, LiteralAssumption, LiteralFeature, __kernel_functions};
+use toml::{Table, Value};
+
+use crate::{AllOf, AnyOf, Assumption, AtLeast, AtMost, Feature, FeatureSet, Negated, PLATFORM_PARAMETERS, lookup_feature, try_resolve_assumptions};
+
+/// Environment variable naming the platform pin read by `#[configurable]` at compile time,
+/// relative to the manifest directory of the crate being built.
+pub const PLATFORM_PIN_VAR: &str = "CONFIGURABLE_PLATFORM_PIN";
+
+// the feature of a literal value, `None` if it names a feature that is not registered
+fn literal_feature(value: &LiteralFeature) -> Option<Arc<dyn Feature>> {
+    match value {
+        LiteralFeature::Named(name) => lookup_feature(name).map(|f| f as Arc<dyn Feature>),
+        LiteralFeature::Exactly(val) => Some(Arc::new(*val)),
+        LiteralFeature::AtLeast(val) => Some(Arc::new(AtLeast { val: *val })),
+        LiteralFeature::AtMost(val) => Some(Arc::new(AtMost { val: *val })),
+        LiteralFeature::AnyOf(alternatives) => {
+            Some(Arc::new(AnyOf::new(alternatives.iter().map(literal_feature).collect::<Option<_>>()?)))
+        }
+    }
+}
+
+// the assumption the dispatcher builds from a literal one, `None` if it names a parameter
+// or a feature that is not declared
+fn literal_assumption(literal: &LiteralAssumption) -> Option<Assumption> {
+    let parameters = PLATFORM_PARAMETERS.lock().unwrap().clone();
+    let mut clauses = Vec::new();
+    for clause in &literal.clauses {
+        let mut by_key: BTreeMap<String, Vec<Arc<dyn Feature>>> = BTreeMap::new();
+        for predicate in clause {
+            if !parameters.contains(&predicate.key) { return None; }
+            let feature = literal_feature(&predicate.value)?;
+            let feature: Arc<dyn Feature> = if predicate.excluded { Arc::new(Negated(feature)) } else { feature };
+            by_key.entry(predicate.key.clone()).or_default().push(feature);
+        }
+        clauses.push(by_key.into_iter()
+            .map(|(key, mut features)| (key, if features.len() == 1 { features.remove(0) } else { Arc::new(AllOf::new(features)) }))
+            .collect::<FeatureSet>());
+    }
+    Some(Assumption::new(clauses).with_priority(literal.priority))
+}
+
+// the variant resolution selects for a kernel function on the current platform, `None` if it
+// cannot be built here or the choice follows the ambiguity policy
+fn pinned_selection(function: &KernelFunction) -> Option<i32> {
+    let assumptions = function.variants.as_ref()?.iter().map(literal_assumption).collect::<Option<Vec<_>>>()?;
+    try_resolve_assumptions(&assumptions).ok()
+}
+
+// the Rust sources under a directory
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            source_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// the pin of kernel functions: functions sharing a key (modules of the same name) are left to run time
+fn pin_table(functions: &[KernelFunction]) -> Table {
+    let mut selections: BTreeMap<&str, Option<(i32, usize)>> = BTreeMap::new();
+    for function in functions {
+        let selection = pinned_selection(function).map(|i| (i, function.variants.as_ref().map_or(0, Vec::len)));
+        selections.entry(&function.key)
+            .and_modify(|s| *s = None)
+            .or_insert(selection);
+    }
+
+    let variants: Table = selections.into_iter()
+        .filter_map(|(key, selection)| {
+            let (selected, count) = selection?;
+            let entry = Table::from_iter([
+                ("selected".to_string(), Value::Integer(selected.into())),
+                ("variants".to_string(), Value::Integer(count as i64)),
+            ]);
+            Some((key.to_string(), Value::Table(entry)))
+        })
+        .collect();
+    Table::from_iter([("variants".to_string(), Value::Table(variants))])
+}
+
+/// The platform pin of the current platform (see `current_platform`) for the kernel modules
+/// of the Rust sources under `sources`, as TOML: the variant resolution selects for each of
+/// their functions, which is what the macro reads.
+///
+/// Functions are left to run-time resolution when their selection cannot be known here: an
+/// assumption is not written literally (values and priorities computed by expressions),
+/// names a parameter or a feature not declared in this process, or the choice follows the
+/// ambiguity policy.
+pub fn platform_pin<P: AsRef<Path>>(sources: P) -> Result<String, Box<dyn Error>> {
+    let mut files = Vec::new();
+    source_files(sources.as_ref(), &mut files)?;
+    files.sort();
+
+    let mut functions = Vec::new();
+    for file in files {
+        let source = fs::read_to_string(&file)?;
+        functions.extend(__kernel_functions(&source, "configurable", "assumptions").map_err(|e| format!("{}: {e}", file.display()))?);
+    }
+    Ok(toml::to_string(&pin_table(&functions))?)
+}
+
+/// Writes `platform_pin(sources)` to a file. Building with `CONFIGURABLE_PLATFORM_PIN` naming
+/// it (from the environment, or `cargo:rustc-env` in a build script) makes the dispatchers of
+/// `#[configurable]` modules call the variant selected for the pinned platform directly,
+/// without the variants they do not call.
+///
+/// The variants are selected in the process writing the pin, by run-time resolution with
+/// the default resolver, so a build script writes it: it declares the platform (e.g. loads
+/// a Platform.toml file, the macro reads only pin files) and the hierarchies and parameters
+/// the crate declares, with `configurable-features` as a build dependency. Dispatchers ignore
+/// a resolver installed at run time (see `check_pinned_resolver`).
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     configurable_features::load_platformaware().unwrap();
+///     let pin = format!("{}/platform-pin.toml", std::env::var("OUT_DIR").unwrap());
+///     configurable_features::export_platform_pin("src", &pin).unwrap();
+///     println!("cargo:rustc-env=CONFIGURABLE_PLATFORM_PIN={pin}");
+///     println!("cargo:rerun-if-changed=src");
+///     println!("cargo:rerun-if-changed=Platform.toml");
+/// }
+/// ```
+pub fn export_platform_pin<P: AsRef<Path>, Q: AsRef<Path>>(sources: P, path: Q) -> Result<(), Box<dyn Error>> {
+    fs::write(path, platform_pin(sources)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use proc_macro2::TokenStream;
+    use quote::quote;
+
+    use super::*;
+    use crate::{create_feature_hierarchy, insert_parameter, with_platform};
+
+    create_feature_hierarchy!{register_pin_test_root ;"pin_test_acc" : None :> PinTestAcc; }
+    create_feature_hierarchy!{register_pin_test_vendor ;"pin_test_acc" : PinTestAcc :> PinTestGpu & PinTestFpga; }
+    create_feature_hierarchy!{register_pin_test_arch ;"pin_test_acc" : PinTestGpu :> PinTestAmpere; }
+    create_feature_hierarchy!{register_pin_test_mem_root ;"pin_test_mem" : None :> PinTestMem; }
+    create_feature_hierarchy!{register_pin_test_mem ;"pin_test_mem" : PinTestMem :> PinTestHbm; }
+
+    fn declare_test_parameters() {
+        insert_parameter("pin_test_acc".to_string(), Arc::new(PinTestAcc));
+        insert_parameter("pin_test_cores".to_string(), Arc::new(AtLeast { val: 0 }));
+        // no top: a missing value stands for the roots of the hierarchy
+        let mut parameters = PLATFORM_PARAMETERS.lock().unwrap();
+        if !parameters.iter().any(|p| p == "pin_test_mem") {
+            parameters.push("pin_test_mem".to_string());
+        }
+    }
+
+    // the kernel functions of a module `kernels` with a function `f` of the given variants
+    fn kernel_functions(variants: &[TokenStream]) -> Vec<KernelFunction> {
+        let source = quote! {
+            #[configurable]
+            mod kernels {
+                #( #[assumptions(#variants)] fn f() {} )*
+            }
+        };
+        __kernel_functions(&source.to_string(), "configurable", "assumptions").unwrap()
+    }
+
+    // the selection of the pin of `platform` and of run-time resolution on it, for variants written
+    // both as attribute arguments and as assumptions
+    fn selections(platform: FeatureSet, variants: &[(TokenStream, Assumption)]) -> (Option<i32>, Option<i32>) {
+        declare_test_parameters();
+        let tokens: Vec<TokenStream> = variants.iter().map(|(t, _)| t.clone()).collect();
+        let assumptions: Vec<Assumption> = variants.iter().map(|(_, a)| a.clone()).collect();
+        with_platform(platform, || {
+            let pin = pin_table(&kernel_functions(&tokens));
+            let entry = pin["variants"].get("kernels::f").cloned();
+            if let Some(entry) = &entry {
+                assert_eq!(entry["variants"].as_integer(), Some(variants.len() as i64));
+            }
+            let pinned = entry.and_then(|e| e["selected"].as_integer()).map(|i| i as i32);
+            (pinned, try_resolve_assumptions(&assumptions).ok())
+        })
+    }
+
+    fn assume<F: Feature + 'static>(parameter: &str, f: F) -> Assumption { FeatureSet::new().with(parameter, f).into() }
+
+    fn node() -> FeatureSet { FeatureSet::new().with("pin_test_acc", PinTestAmpere).with("pin_test_cores", 16) }
+
+    #[test]
+    fn the_pin_selects_the_most_specific_variant() {
+        let variants = [
+            (quote! {}, Assumption::any_platform()),
+            (quote! { pin_test_acc = PinTestGpu }, assume("pin_test_acc", PinTestGpu)),
+            (quote! { pin_test_acc = PinTestAmpere }, assume("pin_test_acc", PinTestAmpere)),
+            (quote! { pin_test_acc = PinTestFpga }, assume("pin_test_acc", PinTestFpga)),
+        ];
+        assert_eq!(selections(node(), &variants), (Some(2), Some(2)));
+        assert_eq!(selections(FeatureSet::new().with("pin_test_acc", PinTestFpga), &variants), (Some(3), Some(3)));
+        assert_eq!(selections(FeatureSet::new(), &variants), (Some(0), Some(0)));
+        assert_eq!(selections(node(), &variants[3..]), (Some(-1), Some(-1)));
+    }
+
+    #[test]
+    fn the_pin_tests_exclusions_as_run_time_resolution() {
+        let gpu = FeatureSet::new().with("pin_test_acc", PinTestGpu);
+        let not_ampere = [
+            (quote! {}, Assumption::any_platform()),
+            (quote! { pin_test_acc != PinTestAmpere }, assume("pin_test_acc", Negated::new(PinTestAmpere))),
+        ];
+        // a supertype of the excluded feature meets the exclusion
+        assert_eq!(selections(gpu.clone(), &not_ampere), (Some(1), Some(1)));
+        assert_eq!(selections(node(), &not_ampere), (Some(0), Some(0)));
+
+        let neither = [
+            (quote! {}, Assumption::any_platform()),
+            (quote! { pin_test_acc != PinTestAmpere | PinTestFpga },
+                assume("pin_test_acc", Negated(Arc::new(AnyOf::new(vec![Arc::new(PinTestAmpere), Arc::new(PinTestFpga)]))))),
+        ];
+        assert_eq!(selections(gpu, &neither), (Some(1), Some(1)));
+
+        let cores = [
+            (quote! {}, Assumption::any_platform()),
+            (quote! { pin_test_cores != AtMost{val: 4} }, assume("pin_test_cores", Negated::new(AtMost { val: 4 }))),
+        ];
+        assert_eq!(selections(node(), &cores), (Some(1), Some(1)));
+        assert_eq!(selections(node().with("pin_test_cores", 2), &cores), (Some(0), Some(0)));
+    }
+
+    #[test]
+    fn the_pin_reads_missing_values_as_run_time_resolution() {
+        let variants = |tokens: TokenStream, assumption: Assumption| [(quote! {}, Assumption::any_platform()), (tokens, assumption)];
+        let selected = |tokens, assumption| selections(node(), &variants(tokens, assumption));
+        // assuming the root is as specific as the fallback, with which it ties
+        assert_eq!(selected(quote! { pin_test_mem = PinTestMem }, assume("pin_test_mem", PinTestMem)), (None, None));
+        assert_eq!(selected(quote! { pin_test_mem = PinTestHbm }, assume("pin_test_mem", PinTestHbm)), (Some(0), Some(0)));
+        assert_eq!(selected(quote! { pin_test_mem != PinTestHbm }, assume("pin_test_mem", Negated::new(PinTestHbm))), (Some(1), Some(1)));
+        assert_eq!(selected(quote! { pin_test_mem != PinTestMem }, assume("pin_test_mem", Negated::new(PinTestMem))), (Some(0), Some(0)));
+        assert_eq!(selected(quote! { pin_test_cores = AtLeast{val: 8} }, assume("pin_test_cores", AtLeast { val: 8 })), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn ties_are_left_to_run_time_resolution() {
+        let variants = [
+            (quote! {}, Assumption::any_platform()),
+            (quote! { pin_test_acc = PinTestGpu }, assume("pin_test_acc", PinTestGpu)),
+            (quote! { pin_test_cores = AtLeast{val: 8} }, assume("pin_test_cores", AtLeast { val: 8 })),
+        ];
+        assert_eq!(selections(node(), &variants), (None, None));
+
+        let mut ranked = variants.clone();
+        ranked[2] = (quote! { pin_test_cores = AtLeast{val: 8}, priority = 3 }, assume("pin_test_cores", AtLeast { val: 8 }).with_priority(3));
+        assert_eq!(selections(node(), &ranked), (Some(2), Some(2)));
+    }
+
+    #[test]
+    fn functions_the_pin_cannot_build_are_left_to_run_time() {
+        declare_test_parameters();
+        let unpinned = |variant: TokenStream| with_platform(node(), || {
+            pin_table(&kernel_functions(&[quote! {}, variant]))["variants"].get("kernels::f").is_none()
+        });
+        assert!(!unpinned(quote! { pin_test_acc = PinTestGpu }));
+        assert!(unpinned(quote! { pin_test_acc = gpu() }));
+        assert!(unpinned(quote! { pin_test_acc = PinTestGpu, priority = P }));
+        assert!(unpinned(quote! { pin_test_acc = PinTestUnregistered }));
+        assert!(unpinned(quote! { pin_test_undeclared = PinTestGpu }));
+    }
+
+    #[test]
+    fn the_pin_covers_the_kernel_modules_of_the_sources() {
+        declare_test_parameters();
+        let sources = std::env::temp_dir().join(format!("configurable-pin-test-{}", std::process::id()));
+        fs::create_dir_all(sources.join("nested")).unwrap();
+        let kernels = |module: &str, variant: &str| format!(
+            "#[configurable]\nmod {module} {{\n #[assumptions]\n fn f() {{}}\n #[assumptions({variant})]\n fn f() {{}}\n}}\n"
+        );
+        fs::write(sources.join("lib.rs"), kernels("gpu_kernels", "pin_test_acc = PinTestGpu")).unwrap();
+        fs::write(sources.join("nested/fpga.rs"), kernels("fpga_kernels", "pin_test_acc = PinTestFpga")).unwrap();
+        // a module name used twice cannot key its functions
+        fs::write(sources.join("nested/twice.rs"), kernels("gpu_kernels", "pin_test_cores = 8")).unwrap();
+
+        let pin_path = sources.join("pin.toml");
+        with_platform(node(), || export_platform_pin(&sources, &pin_path)).unwrap();
+        let pin: Table = fs::read_to_string(&pin_path).unwrap().parse().unwrap();
+        fs::remove_dir_all(&sources).unwrap();
+
+        let variants = pin["variants"].as_table().unwrap();
+        assert_eq!(variants.keys().collect::<Vec<_>>(), vec!["fpga_kernels::f"]);
+        assert_eq!(variants["fpga_kernels::f"]["selected"].as_integer(), Some(0));
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+
+use toml::Value;
+
+use crate::catalog::platformaware::{PLATFORMAWARE_PARAMETERS, PlatformAwareKind};
+use crate::{Feature, PlatformFeatures, QualifierFeature, lookup_feature, report_resolution};
+
+use super::{CURRENT_CONFIG_STRING, CURRENT_FEATURES, update_derived_parameters};
+
+// PlatformAware names that differ from the catalog ones
+const RENAMES: &[(&str, &str)] = &[
+    ("SSE_2", "SSE2"),
+    ("SSE_3", "SSE3"),
+    ("SSSE_3", "SSSE3"),
+    ("SSE_4_1", "SSE4_1"),
+    ("SSE_4_2", "SSE4_2"),
+    ("AVX512", "AVX512F"),
+    ("HIP", "ROCm"),
+    ("oneAPI", "OneAPI"),
+];
+
+fn is_unset(s: &str) -> bool {
+    matches!(s.trim().to_lowercase().as_str(), "" | "unset" | "unknown" | "na")
+}
+
+fn lookup_renamed(name: &str, prefixes: &[&str]) -> Option<Arc<dyn QualifierFeature>> {
+    let name = RENAMES.iter().find(|(from, _)| *from == name).map_or(name, |(_, to)| to);
+    prefixes.iter().find_map(|p| lookup_feature(&format!("{p}{name}")))
+}
+
+// leading number of a string such as "2.6 GHz" or "16 GB", and the rest of it
+fn split_number(s: &str) -> Option<(f64, String)> {
+    let s = s.trim();
+    let end = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let n = s[..end].parse::<f64>().ok()?;
+    Some((n, s[end..].trim().to_lowercase()))
+}
+
+fn read_count(v: &Value) -> Option<i32> {
+    match v {
+        Value::Integer(i) => i32::try_from(*i).ok(),
+        Value::Float(f) => Some(*f as i32),
+        Value::String(s) if !is_unset(s) => split_number(s).map(|(n, _)| n as i32),
+        _ => None,
+    }
+}
+
+// sizes in MiB; numbers without unit are bytes
+fn read_size(v: &Value) -> Option<i32> {
+    let (n, unit) = match v {
+        Value::Integer(i) => (*i as f64, String::new()),
+        Value::Float(f) => (*f, String::new()),
+        Value::String(s) if !is_unset(s) => split_number(s)?,
+        _ => return None,
+    };
+    let mib = match unit.trim_end_matches("ib").trim_end_matches('b') {
+        "" => n / (1024.0 * 1024.0),
+        "k" => n / 1024.0,
+        "m" => n,
+        "g" => n * 1024.0,
+        "t" => n * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(mib as i32)
+}
+
+fn read_qualifier(v: &Value, prefixes: &[&str]) -> Option<Arc<dyn Feature>> {
+    match v {
+        Value::String(s) if !is_unset(s) => lookup_renamed(s, prefixes).map(|f| f as Arc<dyn Feature>),
+        _ => None,
+    }
+}
+
+// e.g. accelerator_api = ["CUDA_5_0", "OpenCL_3_0", "unset"]
+fn read_qualifier_list(v: &Value) -> Option<Arc<dyn Feature>> {
+    let Value::Array(items) = v else { return read_qualifier(v, &[""]) };
+
+    let mut features: Vec<Arc<dyn QualifierFeature>> = Vec::new();
+    for item in items {
+        let Value::String(s) = item else { continue };
+        if is_unset(s) { continue; }
+        let name = s.split('_').next().unwrap_or(s);
+        if let Some(f) = lookup_renamed(name, &[""])
+            && !features.iter().any(|g| g.string() == f.string()) {
+            features.push(f);
+        }
+    }
+
+    match features.len() {
+        0 => None,
+        1 => Some(features.remove(0) as Arc<dyn Feature>),
+        _ => Some(Arc::new(features)),
+    }
+}
+
+/// Reads the platform features of a Platform.toml file written for PlatformAware.jl.
+///
+/// Entries that are `"unset"`/`"unknown"` are skipped. So are the qualifiers unknown to the feature
+/// map, with a warning (see `set_resolution_reporter`), and the unknown items of `accelerator_api`.
+pub fn read_platformaware_features(contents: &str) -> Result<PlatformFeatures, Box<dyn Error>> {
+    let table: toml::Table = toml::from_str(contents)?;
+
+    // parameters are grouped in sections ([node], [processor], ...); their names are unique across sections
+    let mut entries: HashMap<String, Value> = HashMap::new();
+    for (key, value) in table {
+        match value {
+            Value::Table(section) => entries.extend(section),
+            other => { entries.insert(key, other); }
+        }
+    }
+
+    let mut m = PlatformFeatures::new();
+    for (name, kind) in PLATFORMAWARE_PARAMETERS {
+        let Some(v) = entries.get(*name) else { continue };
+        let f: Option<Arc<dyn Feature>> = match kind {
+            PlatformAwareKind::Count => read_count(v).map(|n| Arc::new(n) as Arc<dyn Feature>),
+            PlatformAwareKind::Size => read_size(v).map(|n| Arc::new(n) as Arc<dyn Feature>),
+            PlatformAwareKind::Qualifier(prefixes) => {
+                let f = read_qualifier(v, prefixes);
+                if let (None, Value::String(s)) = (&f, v) && !is_unset(s) {
+                    report_resolution(format!("warning: unknown {name} `{s}` in Platform.toml, skipped"));
+                }
+                f
+            }
+            PlatformAwareKind::QualifierList => read_qualifier_list(v),
+        };
+        if let Some(f) = f { m.insert(name.to_string(), f); }
+    }
+
+    Ok(m)
+}
+
+/// Loads the Platform.toml file (located as for `readplatform`) in the PlatformAware.jl schema
+/// into `CURRENT_FEATURES`, then evaluates the derived parameters.
+pub fn load_platformaware() -> Result<(), Box<dyn Error>> {
+    let contents = CURRENT_CONFIG_STRING.clone().ok_or("error reading Platform.toml")?;
+    let features = read_platformaware_features(&contents)?;
+    CURRENT_FEATURES.lock().unwrap().extend(features);
+    update_derived_parameters();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::collect_reports;
+    use super::*;
+
+    fn strings(m: &PlatformFeatures) -> Vec<(String, String)> {
+        let mut v: Vec<(String, String)> = m.iter().map(|(k, f)| (k.clone(), f.string())).collect();
+        v.sort();
+        v
+    }
+
+    fn pairs(v: &[(&str, &str)]) -> Vec<(String, String)> {
+        v.iter().map(|(k, f)| (k.to_string(), f.to_string())).collect()
+    }
+
+    #[test]
+    fn reads_a_platformaware_file() {
+        let mut features = PlatformFeatures::new();
+        let reported = collect_reports("GeForce_RTX_2060", || {
+            features = read_platformaware_features(include_str!("testdata/Platform.toml")).unwrap();
+        });
+        assert_eq!(strings(&features), pairs(&[
+            ("accelerator_api", "[CUDA,OpenCL,]"),
+            ("accelerator_architecture", "NVIDIA_GPU_Turing"),
+            ("accelerator_count", "exactly 1"),
+            ("accelerator_manufacturer", "Accelerator_NVIDIA"),
+            ("accelerator_memory_size", "exactly 6144"),
+            ("accelerator_processor_count", "exactly 1920"),
+            ("accelerator_tdp", "exactly 160"),
+            ("accelerator_type", "GPU"),
+            ("node_count", "exactly 1"),
+            ("node_memory_frequency", "exactly 2666"),
+            ("node_memory_size", "exactly 16384"),
+            ("node_threads_count", "exactly 1"),
+            ("processor_core_clock", "exactly 3"),
+            ("processor_core_count", "exactly 10"),
+            ("processor_core_threads_count", "exactly 2"),
+            ("processor_count", "exactly 1"),
+            ("processor_manufacturer", "Intel"),
+            ("processor_microarchitecture", "Intel_Skylake"),
+            ("processor_simd", "AVX512F"),
+            ("processor_tdp", "exactly 125"),
+            ("storage_size", "exactly 953869"),
+        ]));
+        assert_eq!(reported, vec!["warning: unknown accelerator `GeForce_RTX_2060` in Platform.toml, skipped"]);
+    }
+
+    #[test]
+    fn accelerator_manufacturers_are_not_processor_vendors() {
+        let features = read_platformaware_features("[accelerator]\naccelerator_type = \"FPGA\"\naccelerator_manufacturer = \"Xilinx\"\n").unwrap();
+        assert_eq!(strings(&features), pairs(&[("accelerator_manufacturer", "Accelerator_Xilinx"), ("accelerator_type", "FPGA")]));
+    }
+
+    #[test]
+    fn sizes_are_read_in_mib() {
+        assert_eq!(read_size(&Value::String("16 GB".to_string())), Some(16384));
+        assert_eq!(read_size(&Value::String("512 MiB".to_string())), Some(512));
+        assert_eq!(read_size(&Value::String("1 TB".to_string())), Some(1024 * 1024));
+        assert_eq!(read_size(&Value::String("64kB".to_string())), Some(0));
+        assert_eq!(read_size(&Value::Integer(1 << 30)), Some(1024));
+        assert_eq!(read_size(&Value::String("unset".to_string())), None);
+        assert_eq!(read_size(&Value::String("12 parsecs".to_string())), None);
+        assert_eq!(read_count(&Value::String("2.6 GHz".to_string())), Some(2));
+    }
+
+    #[test]
+    fn names_are_translated_and_prefixed() {
+        let name = |s: &str, prefixes: &[&str]| lookup_renamed(s, prefixes).map(|f| f.string());
+        assert_eq!(name("SSE_4_2", &[""]).as_deref(), Some("SSE4_2"));
+        assert_eq!(name("HIP", &[""]).as_deref(), Some("ROCm"));
+        assert_eq!(name("Ampere", &["", "NVIDIA_GPU_"]).as_deref(), Some("NVIDIA_GPU_Ampere"));
+        assert_eq!(name("Zen4", &["", "Intel_", "AMD_"]).as_deref(), Some("AMD_Zen4"));
+        assert_eq!(name("Ampere", &[""]), None);
+
+        let list = |items: &[&str]| read_qualifier_list(&Value::Array(items.iter().map(|s| Value::String(s.to_string())).collect())).map(|f| f.string());
+        assert_eq!(list(&["HIP_5_7", "unset", "OpenCL_2_0", "OpenCL_3_0"]).as_deref(), Some("[ROCm,OpenCL,]"));
+        assert_eq!(list(&["oneAPI", "OpenACC"]).as_deref(), Some("OneAPI"));
+        assert_eq!(list(&["unset", "OpenGL_4_6"]), None);
+    }
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fmt, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::Assumption;
+
+/// How `resolve` chooses between compatible variants none of which is more specific than the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AmbiguityPolicy {
+    /// no choice among them: dispatchers call the fallback variant (the one without assumptions).
+    /// To fail on ambiguity instead, resolve with `try_resolve_assumptions`, which returns
+    /// the `Ambiguity` whatever the policy.
+    Fallback,
+    /// the candidate declared first
+    FirstDeclared,
+    /// the candidate declared last
+    #[default]
+    LastDeclared,
+}
+
+impl AmbiguityPolicy {
+    /// The candidate selected among ambiguous ones (in declaration order), `None` for `Fallback`.
+    pub fn choose(self, candidates: &[usize]) -> Option<usize> {
+        match self {
+            AmbiguityPolicy::Fallback => None,
+            AmbiguityPolicy::FirstDeclared => candidates.first().copied(),
+            AmbiguityPolicy::LastDeclared => candidates.last().copied(),
+        }
+    }
+}
+
+pub static AMBIGUITY_POLICY: Lazy<Mutex<AmbiguityPolicy>> = Lazy::new(|| Mutex::new(AmbiguityPolicy::default()));
+
+pub fn set_ambiguity_policy(policy: AmbiguityPolicy) {
+    *AMBIGUITY_POLICY.lock().unwrap() = policy;
+}
+
+pub fn ambiguity_policy() -> AmbiguityPolicy { *AMBIGUITY_POLICY.lock().unwrap() }
+
+/// Compatible variants none of which is more specific than all the others.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ambiguity {
+    /// indices of the candidates, in declaration order
+    pub candidates: Vec<usize>,
+    pub assumptions: Vec<Assumption>,
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let candidates: Vec<String> = self.candidates.iter().map(|&i| format!("#{i} {}", self.assumptions[i])).collect();
+        write!(f, "ambiguous kernel variants, none is more specific than the others: {}", candidates.join(", "))
+    }
+}
+
+impl Error for Ambiguity {}
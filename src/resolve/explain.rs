@@ -0,0 +1,221 @@
+use std::fmt;
+
+use crate::{Assumption, FEATURE_TOP, FeatureSet, PLATFORM_PARAMETERS, PlatformParameter, display_features};
+
+use super::{AmbiguityPolicy, ambiguity_policy, current_platform, dominating, maximal, parameter_satisfies, select};
+
+/// Check of one parameter constrained by a clause.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterTrace {
+    pub parameter: PlatformParameter,
+    /// the platform feature, `None` if the platform does not declare the parameter
+    pub platform: Option<String>,
+    /// the declared top of the parameter, which stands for a missing platform feature
+    pub top: Option<String>,
+    pub assumed: String,
+    pub compatible: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClauseTrace {
+    pub parameters: Vec<ParameterTrace>,
+    /// keys of the clause that are not platform parameters, which the check ignores
+    pub ignored: Vec<String>,
+    pub compatible: bool,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CandidateTrace {
+    pub index: usize,
+    pub assumption: Assumption,
+    pub clauses: Vec<ClauseTrace>,
+    pub compatible: bool,
+    /// compatible candidates strictly more specific than this one, if it is compatible
+    pub dominated_by: Vec<usize>,
+}
+
+/// How a resolution ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResolutionOutcome {
+    /// the index of the selected candidate
+    Selected(usize),
+    /// no candidate is compatible
+    NoneCompatible,
+    /// the candidates tied and the policy was `AmbiguityPolicy::Fallback` (the fallback is called)
+    Refused,
+}
+
+/// Explanation of a resolution, see `resolve_explain`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResolutionTrace {
+    pub platform: FeatureSet,
+    pub candidates: Vec<CandidateTrace>,
+    /// the compatible candidates no other is more specific than
+    pub maximal: Vec<usize>,
+    /// the maximal candidates sharing the highest priority, when there are several
+    pub ambiguous: Vec<usize>,
+    pub policy: AmbiguityPolicy,
+    pub outcome: ResolutionOutcome,
+}
+
+impl ResolutionTrace {
+
+    /// The index `resolve_assumptions` returns, `-1` unless a candidate is selected.
+    pub fn choice(&self) -> i32 {
+        match self.outcome {
+            ResolutionOutcome::Selected(index) => index as i32,
+            _ => -1,
+        }
+    }
+}
+
+fn trace_clause(platform: &FeatureSet, clause: &FeatureSet) -> ClauseTrace {
+    let tops = FEATURE_TOP.lock().unwrap();
+    let registered = PLATFORM_PARAMETERS.lock().unwrap();
+    let parameters: Vec<ParameterTrace> = registered.iter()
+        .filter_map(|p| {
+            let assumed = clause.get(p)?;
+            let value = platform.get(p);
+            Some(ParameterTrace {
+                parameter: p.clone(),
+                platform: value.map(|v| v.string()),
+                top: tops.get(p).map(|t| t.string()),
+                assumed: assumed.string(),
+                compatible: parameter_satisfies(value, Some(assumed), tops.get(p)),
+            })
+        })
+        .collect();
+    let mut ignored: Vec<String> = clause.keys().filter(|k| !registered.contains(k)).cloned().collect();
+    ignored.sort();
+    let compatible = parameters.iter().all(|p| p.compatible);
+    ClauseTrace { parameters, ignored, compatible }
+}
+
+/// Resolves like `resolve_assumptions`, recording for each candidate which constrained
+/// parameters the platform satisfies and which candidates are more specific.
+///
+/// The trace prints as a report (`{}`). It explains the `MostSpecific` resolver, whatever
+/// the installed one.
+pub fn resolve_explain(assumption_list: &[Assumption]) -> ResolutionTrace {
+    let platform = current_platform();
+
+    let mut candidates: Vec<CandidateTrace> = assumption_list.iter().enumerate()
+        .map(|(index, assumption)| {
+            let clauses: Vec<ClauseTrace> = assumption.clauses.iter().map(|c| trace_clause(&platform, c)).collect();
+            let compatible = clauses.iter().any(|c| c.compatible);
+            CandidateTrace { index, assumption: assumption.clone(), clauses, compatible, dominated_by: Vec::new() }
+        })
+        .collect();
+
+    let compatible: Vec<usize> = candidates.iter().filter(|c| c.compatible).map(|c| c.index).collect();
+    for &i in &compatible {
+        candidates[i].dominated_by = dominating(assumption_list, &compatible, i);
+    }
+
+    let maximal = maximal(assumption_list, &compatible);
+    let policy = ambiguity_policy();
+    let (ambiguous, outcome) = match select(assumption_list, &compatible) {
+        Ok(-1) => (Vec::new(), ResolutionOutcome::NoneCompatible),
+        Ok(choice) => (Vec::new(), ResolutionOutcome::Selected(choice as usize)),
+        Err(candidates) => {
+            let outcome = policy.choose(&candidates).map_or(ResolutionOutcome::Refused, ResolutionOutcome::Selected);
+            (candidates, outcome)
+        }
+    };
+
+    ResolutionTrace { platform, candidates, maximal, ambiguous, policy, outcome }
+}
+
+fn indices(list: &[usize]) -> String {
+    list.iter().map(|i| format!("#{i}")).collect::<Vec<String>>().join(", ")
+}
+
+impl fmt::Display for ResolutionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "platform: {}", display_features(&self.platform))?;
+
+        for c in &self.candidates {
+            let status = match (c.compatible, c.dominated_by.is_empty()) {
+                (false, _) => "incompatible".to_string(),
+                (true, true) => "compatible".to_string(),
+                (true, false) => format!("compatible, less specific than {}", indices(&c.dominated_by)),
+            };
+            writeln!(f, "#{} {}: {status}", c.index, c.assumption)?;
+
+            let indent = if c.clauses.len() > 1 { "        " } else { "    " };
+            for (k, clause) in c.clauses.iter().enumerate() {
+                if c.clauses.len() > 1 {
+                    writeln!(f, "    clause {k}: {}", if clause.compatible { "compatible" } else { "incompatible" })?;
+                }
+                for p in &clause.parameters {
+                    let platform = match (&p.platform, &p.top) {
+                        (Some(v), _) => v.clone(),
+                        (None, Some(top)) => format!("missing (top {top})"),
+                        (None, None) => "missing".to_string(),
+                    };
+                    let relation = if p.compatible { "<:" } else { "is not a subtype of" };
+                    writeln!(f, "{indent}{}: {platform} {relation} {}", p.parameter, p.assumed)?;
+                }
+                for key in &clause.ignored {
+                    writeln!(f, "{indent}{key}: ignored, not a platform parameter")?;
+                }
+            }
+        }
+
+        if self.maximal.len() > 1 && self.ambiguous.is_empty() {
+            writeln!(f, "incomparable: {}, ranked by priority", indices(&self.maximal))?;
+        }
+        if !self.ambiguous.is_empty() {
+            writeln!(f, "ambiguous: {} ({:?})", indices(&self.ambiguous), self.policy)?;
+        }
+        match self.outcome {
+            ResolutionOutcome::Selected(choice) => write!(f, "selected: #{choice}"),
+            ResolutionOutcome::NoneCompatible => write!(f, "selected: none, no compatible variant"),
+            ResolutionOutcome::Refused => write!(f, "selected: none, ambiguous under {:?} (the fallback is called)", self.policy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::with_platform;
+    use crate::testing::{TestGpu, avx, fpga, gpu, node, with_policy};
+    use super::*;
+
+    #[test]
+    fn outcomes_separate_incompatibility_from_refusal() {
+        with_platform(node(), || {
+            let trace = resolve_explain(&[Assumption::any_platform(), gpu(), fpga()]);
+            assert_eq!(trace.outcome, ResolutionOutcome::Selected(1));
+            assert_eq!(trace.candidates[0].dominated_by, vec![1]);
+            assert!(!trace.candidates[2].compatible);
+
+            let trace = resolve_explain(&[fpga()]);
+            assert_eq!((trace.outcome, trace.choice()), (ResolutionOutcome::NoneCompatible, -1));
+            assert!(trace.to_string().ends_with("selected: none, no compatible variant"));
+
+            let trace = with_policy(AmbiguityPolicy::Fallback, || resolve_explain(&[Assumption::any_platform(), gpu(), avx()]));
+            assert_eq!((trace.outcome, trace.choice()), (ResolutionOutcome::Refused, -1));
+            assert_eq!(trace.ambiguous, vec![1, 2]);
+            assert!(trace.to_string().contains("ambiguous under Fallback"));
+        });
+    }
+
+    #[test]
+    fn unknown_keys_are_listed_as_ignored() {
+        with_platform(node(), || {
+            let clause = FeatureSet::new().with("test_acc", TestGpu).with("explain_test_unknown", 1);
+            let trace = resolve_explain(&[Assumption::new(vec![clause])]);
+            let clause = &trace.candidates[0].clauses[0];
+            assert_eq!(clause.ignored, vec!["explain_test_unknown"]);
+            assert_eq!(clause.parameters.len(), 1);
+            assert!(clause.compatible);
+            assert!(trace.to_string().contains("explain_test_unknown: ignored, not a platform parameter"));
+        });
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Mutex;
+
+/// Variants a dispatcher in `fallback_on_error` mode tries in turn (see `#[configurable]`),
+/// remembering those that failed so that later calls skip them.
+pub struct FallbackChain {
+    ranked: Vec<usize>,
+    failed: Mutex<Vec<usize>>,
+}
+
+impl FallbackChain {
+
+    /// The chain of a ranking (see `rank_assumptions`), which always ends with the fallback.
+    pub fn new(mut ranked: Vec<usize>, fallback: usize) -> Self {
+        ranked.retain(|&i| i != fallback);
+        ranked.push(fallback);
+        FallbackChain { ranked, failed: Mutex::new(Vec::new()) }
+    }
+
+    /// The variants to try, in order: those that have not failed, then the fallback.
+    pub fn candidates(&self) -> Vec<usize> {
+        let failed = self.failed.lock().unwrap();
+        self.ranked.iter().copied().filter(|i| !failed.contains(i)).collect()
+    }
+
+    /// Records a failure of a variant. The fallback is never skipped.
+    pub fn mark_failed(&self, variant: usize) {
+        let mut failed = self.failed.lock().unwrap();
+        if self.ranked.last() != Some(&variant) && !failed.contains(&variant) {
+            failed.push(variant);
+        }
+    }
+
+    pub fn failed(&self) -> Vec<usize> { self.failed.lock().unwrap().clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{FeatureSet, configurable, with_platform};
+    use crate::testing::{collect_reports, declare_test_parameters, node};
+
+    #[test]
+    fn the_fallback_ends_the_chain() {
+        let chain = FallbackChain::new(vec![2, 0, 1], 0);
+        assert_eq!(chain.candidates(), vec![2, 1, 0]);
+        assert_eq!(FallbackChain::new(vec![2], 0).candidates(), vec![2, 0]);
+    }
+
+    #[test]
+    fn failed_variants_are_skipped_but_the_fallback() {
+        let chain = FallbackChain::new(vec![2, 1, 0], 0);
+        chain.mark_failed(2);
+        chain.mark_failed(2);
+        chain.mark_failed(0);
+        assert_eq!(chain.failed(), vec![2]);
+        assert_eq!(chain.candidates(), vec![1, 0]);
+    }
+
+    thread_local! {
+        static CLONES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // an argument counting its copies
+    struct Input(usize);
+
+    impl Clone for Input {
+        fn clone(&self) -> Self {
+            CLONES.with(|c| c.set(c.get() + 1));
+            Input(self.0)
+        }
+    }
+
+    #[configurable(fallback_on_error)]
+    mod chained {
+        use super::Input;
+        use crate::testing::{TestAmpere, TestGpu};
+
+        #[assumptions]
+        pub fn run(input: Input, log: &mut Vec<&'static str>) -> Result<usize, String> {
+            log.push("fallback");
+            if input.0 == 0 { Err("fallback failed".to_string()) } else { Ok(0) }
+        }
+
+        #[assumptions(test_acc = TestGpu)]
+        pub fn run(input: Input, log: &mut Vec<&'static str>) -> Result<usize, String> {
+            log.push("gpu");
+            if input.0 < 2 { Err("gpu failed".to_string()) } else { Ok(input.0) }
+        }
+
+        #[assumptions(test_acc = TestAmpere)]
+        pub fn run(_input: Input, log: &mut Vec<&'static str>) -> Result<usize, String> {
+            log.push("ampere");
+            Err("ampere failed".to_string())
+        }
+
+        #[assumptions]
+        pub fn retried() -> Result<(), String> { Ok(()) }
+
+        #[assumptions(test_acc = TestGpu)]
+        pub fn retried() -> Result<(), String> { Err("gpu failed".to_string()) }
+    }
+
+    // the result, the variants called and the copies of the input
+    fn run_on(platform: FeatureSet, input: usize) -> (Result<usize, String>, Vec<&'static str>, usize) {
+        declare_test_parameters();
+        CLONES.with(|c| c.set(0));
+        let mut log = Vec::new();
+        let result = with_platform(platform, || chained::run(Input(input), &mut log));
+        (result, log, CLONES.with(Cell::get))
+    }
+
+    #[test]
+    fn failing_variants_pass_on_to_the_next() {
+        assert_eq!(run_on(node(), 3), (Ok(3), vec!["ampere", "gpu"], 2));
+        assert_eq!(run_on(node(), 1), (Ok(0), vec!["ampere", "gpu", "fallback"], 2));
+    }
+
+    #[test]
+    fn the_error_of_the_fallback_is_returned() {
+        assert_eq!(run_on(node(), 0), (Err("fallback failed".to_string()), vec!["ampere", "gpu", "fallback"], 2));
+    }
+
+    #[test]
+    fn the_last_candidate_takes_the_arguments() {
+        assert_eq!(run_on(FeatureSet::new(), 1), (Ok(0), vec!["fallback"], 0));
+    }
+
+    #[test]
+    fn every_failure_is_reported() {
+        declare_test_parameters();
+        // scoped calls do not remember failures
+        let reported = collect_reports("of `retried` failed", || with_platform(node(), || {
+            assert_eq!(chained::retried(), Ok(()));
+            assert_eq!(chained::retried(), Ok(()));
+        }));
+        assert_eq!(reported, vec!["warning: variant #1 of `retried` failed, trying #0"; 2]);
+    }
+}
@@ -1,90 +1,327 @@
 use std::{collections::HashMap, sync::Arc};
 
-use crate::{CURRENT_FEATURES, Feature, PLATFORM_PARAMETERS, PlatformParameter, featuremap::FEATURE_MAP};
+mod ambiguity;
+mod explain;
+mod fallback;
+mod ranking;
+mod report;
+mod resolver;
+mod scoped;
 
-// The glorious resolution algorithm
+pub use ambiguity::*;
+pub use explain::*;
+pub use fallback::*;
+pub use ranking::*;
+pub use report::*;
+pub use resolver::*;
+pub use scoped::*;
+
+use crate::{Assumption, CURRENT_FEATURES, FEATURE_TOP, Feature, FeatureObj, FeatureSet, PLATFORM_PARAMETERS, PlatformParameter, complete_platform_features};
+
+// The glorious resolution algorithm (with the default resolver, `resolve_all(..).selected()`, `-1` for `None`)
 pub fn resolve(featureset_list:Vec<HashMap<PlatformParameter, Arc<dyn Feature>>> ) -> i32
 {
-    // println!("ENTER RESOLVE");
+    resolve_assumptions(featureset_list.into_iter().map(Assumption::from).collect())
+}
 
-    let actualplatformfeatures: HashMap<PlatformParameter, Arc<dyn Feature>> = CURRENT_FEATURES.lock().unwrap().clone();
+// Resolution over assumptions in disjunctive normal form (see `Assumption`), by the
+// installed resolver (see `set_resolver`)
+pub fn resolve_assumptions(assumption_list:Vec<Assumption>) -> i32
+{
+    resolve_with(&*resolver(), assumption_list)
+}
 
-    /* println!("Tamanho da tabela: {}", actualplatformfeatures.len());
-    for (key, feature) in &actualplatformfeatures {
-        println!("{}: {}", key, feature.string());
-    }
+/// Resolution against the current platform (see `current_platform`) by a given resolver,
+/// as dispatchers of modules declared with `#[configurable(resolver = ...)]` do.
+pub fn resolve_with(resolver: &dyn Resolver, assumption_list: Vec<Assumption>) -> i32 {
+    check_assumptions(&assumption_list);
+    resolver.resolve(&current_platform(), &assumption_list)
+}
 
-        let dict = FEATURE_MAP.lock().unwrap();
-     for (key, feature) in &dict.clone() {
-        println!("+++ {}: {}", key, feature.string());
-    }*/
+/// Ranking of the assumptions against the current platform by the installed resolver (see
+/// `Resolver::rank`), as dispatchers in `fallback_on_error` mode use it.
+pub fn rank_assumptions(assumption_list: Vec<Assumption>) -> Vec<usize> {
+    rank_with(&*resolver(), assumption_list)
+}
 
+pub fn rank_with(resolver: &dyn Resolver, assumption_list: Vec<Assumption>) -> Vec<usize> {
+    check_assumptions(&assumption_list);
+    resolver.rank(&current_platform(), &assumption_list)
+}
 
-    // i points to the current candidate in the featureset_list
-    let mut i: i32 = (featureset_list.len()-1).try_into().unwrap();
-    let mut current_choice: Option<&HashMap<PlatformParameter,Arc<dyn Feature>>> = None;
-    let mut current_choice_index  = -1;
+/// Index of the most specific assumption compatible with the current platform (see
+/// `current_platform`), `-1` if none is compatible, or the candidates between which
+/// the choice is ambiguous.
+///
+/// Among compatible assumptions none of which is more specific than the others, the one
+/// with the highest priority is selected; the choice is ambiguous if several share it.
+/// Equivalent assumptions (each at least as specific as the other, e.g. the same one written
+/// twice) are incomparable too: they tie unless their priorities differ.
+pub fn try_resolve_assumptions(assumption_list: &[Assumption]) -> Result<i32, Ambiguity> {
+    check_assumptions(assumption_list);
+    most_specific(&current_platform(), assumption_list)
+}
 
-    while i >= 0 {
-        // look for the next candidate that is compatible with actualplatformfeatures and is more specific than current_choice, if it is defined
-        while i >= 0 && !(issubtypeof(&actualplatformfeatures, &featureset_list[i as usize]) 
-                                   && (current_choice.is_none() || 
-                                       issubtypeof(&featureset_list[i as usize], &current_choice.unwrap()))) { 
-            i -= 1;
+// unknown keys and ill-kinded values are ignored by the subtype test, report them in debug builds
+// (see `report_resolution`), with the errors of the startup declarations
+fn check_assumptions(assumption_list: &[Assumption]) {
+    crate::report_parameter_errors();
+    #[cfg(debug_assertions)]
+    for assumption in assumption_list {
+        if let Err(e) = crate::check_assumption(assumption) {
+            report_resolution(format!("warning: {e}"));
         }
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = assumption_list;
+}
+
+// the first candidate of `resolve_all_on`, unless the ambiguity policy had to choose it
+fn most_specific(platform: &FeatureSet, assumption_list: &[Assumption]) -> Result<i32, Ambiguity> {
+    select(assumption_list, &compatible(platform, assumption_list))
+        .map_err(|candidates| Ambiguity { candidates, assumptions: assumption_list.to_vec() })
+}
+
+// indices of the assumptions compatible with the platform
+fn compatible(platform: &FeatureSet, assumption_list: &[Assumption]) -> Vec<usize> {
+    (0..assumption_list.len())
+        .filter(|&i| is_compatible(platform, &assumption_list[i]))
+        .collect()
+}
 
-        if i >= 0 {
-            // if i >= 0, we found a candidate and current choice must be updated
-            current_choice = Some(&featureset_list[i as usize]);
-            current_choice_index = i;
-            i -= 1;
+// the candidate the ambiguity policy selects, the fallback (`-1`) under `AmbiguityPolicy::Fallback`,
+// with a warning (see `report_resolution`)
+fn settle(ambiguity: Ambiguity) -> i32 {
+    let policy = ambiguity_policy();
+    match policy.choose(&ambiguity.candidates) {
+        Some(index) => {
+            report_resolution(format!("warning: {ambiguity}; selecting #{index} ({policy:?})"));
+            index as i32
+        }
+        None => {
+            report_resolution(format!("warning: {ambiguity}; selecting the fallback ({policy:?})"));
+            -1
         }
     }
+}
+
+// compatible candidates strictly more specific than candidate i
+fn dominating(assumption_list: &[Assumption], compatible: &[usize], i: usize) -> Vec<usize> {
+    compatible.iter().copied()
+        .filter(|&j| is_more_specific(&assumption_list[j], &assumption_list[i]) && !is_more_specific(&assumption_list[i], &assumption_list[j]))
+        .collect()
+}
+
+// the compatible candidates no other is strictly more specific than, in declaration order
+fn maximal(assumption_list: &[Assumption], compatible: &[usize]) -> Vec<usize> {
+    compatible.iter().copied()
+        .filter(|&i| dominating(assumption_list, compatible, i).is_empty())
+        .collect()
+}
+
+// the maximal candidate of highest priority, or the ambiguous candidates sharing it
+fn select(assumption_list: &[Assumption], compatible: &[usize]) -> Result<i32, Vec<usize>> {
+    let maximal = maximal(assumption_list, compatible);
+    let top = maximal.iter().map(|&i| assumption_list[i].priority).max();
+    let ranked: Vec<usize> = maximal.into_iter().filter(|&i| Some(assumption_list[i].priority) == top).collect();
+
+    match ranked.len() {
+        0 => Ok(-1),
+        1 => Ok(ranked[0] as i32),
+        _ => Err(ranked),
+    }
+}
+
+/// The features resolution runs against: `CURRENT_FEATURES`, or the platform of the current
+/// `with_platform` scope, completed with the parameter defaults and the derived parameters.
+pub fn current_platform() -> FeatureSet {
+    let mut features = match platform_override() {
+        Some(platform) => platform.into_map(),
+        None => CURRENT_FEATURES.lock().unwrap().clone(),
+    };
+    complete_platform_features(&mut features);
+    features.into()
+}
 
-    // println!("EXIT RESOLVE {}", current_choice_index);
+// a platform is compatible with an assumption if it satisfies at least one of its clauses
+pub fn is_compatible(platform: &HashMap<PlatformParameter, Arc<dyn Feature>>, assumption: &Assumption) -> bool {
+    assumption.clauses.iter().any(|clause| satisfies(platform, clause))
+}
 
-    current_choice_index 
+// lhs is at least as specific as rhs if each clause of lhs is a subtype of some clause of rhs
+// (for single-clause assumptions, this is issubtypeof)
+pub fn is_more_specific(lhs: &Assumption, rhs: &Assumption) -> bool {
+    lhs.clauses.iter().all(|l| rhs.clauses.iter().any(|r| issubtypeof(l, r)))
 }
     
 
 // check whether the left set of features is a subtype of the right set of features (compatibilty relation)
+//
+// a parameter missing on the right is unconstrained; a parameter missing on the left takes the
+// declared top of the parameter (see `insert_parameter`)
 pub fn issubtypeof(lhs: &HashMap<PlatformParameter, Arc<dyn Feature>>, rhs: &HashMap<PlatformParameter, Arc<dyn Feature>>) -> bool {
 
-   // println!("subtype test"); // DEBUG
+    let tops = FEATURE_TOP.lock().unwrap();
 
-    for p in PLATFORM_PARAMETERS.lock().unwrap().iter()  {
+    PLATFORM_PARAMETERS.lock().unwrap().iter()
+        .all(|p| parameter_subtype(lhs.get(p), rhs.get(p), tops.get(p)))
+}
 
-        //print!("subtype test - parameter {:?}", p); // DEBUG
-        
-        let vl = lhs.get(p);
-        let vr = rhs.get(p);
+// check whether the platform features meet every constraint of a clause (see `FeatureObj::satisfies`)
+//
+// parameters are handled as in `issubtypeof`: a missing platform value takes the declared top
+pub fn satisfies(platform: &HashMap<PlatformParameter, Arc<dyn Feature>>, clause: &HashMap<PlatformParameter, Arc<dyn Feature>>) -> bool {
 
-        // begin DEBUG
-     /*     match vl {
-            Some(s) => print!(" {}", s.string()),
-            None => print!(" {}", "no"),
-        };
+    let tops = FEATURE_TOP.lock().unwrap();
 
-        match vr {
-            Some(s) => print!(" {}", s.string()),
-            None => print!(" {}", "no"),
-        };*/  
-        // end DEBUG
+    PLATFORM_PARAMETERS.lock().unwrap().iter()
+        .all(|p| parameter_satisfies(platform.get(p), clause.get(p), tops.get(p)))
+}
 
-        let issubtype = match vl {
-                                None => match vr {
-                                    Some(vrt) => vrt.supertype().is_none(),
-                                    None => true,
-                                }
-                                Some(vlt) => match vr {
-                                    Some(vrt) => vlt.feature_obj().subtypeof(&vrt.feature_obj()),
-                                    None => true, 
-                                }
-                              };
-        //println!(" {}", issubtype); // DEBUG
-        if !issubtype { return false; }
-    } 
+// compatibility test of the platform value of one parameter, given its declared top
+fn parameter_satisfies(vl: Option<&Arc<dyn Feature>>, vr: Option<&Arc<dyn Feature>>, top: Option<&Arc<dyn Feature>>) -> bool {
+    match (vl, vr) {
+        (_, None) => true,
+        (Some(vlt), Some(vrt)) => vlt.feature_obj().satisfies(&vrt.feature_obj()),
+        (None, Some(vrt)) => match top {
+            Some(top) => top.feature_obj().satisfies(&vrt.feature_obj()),
+            None => satisfied_when_missing(vrt.as_ref()),
+        },
+    }
+}
 
-    true
+// subtype test of the values of one parameter, given its declared top
+fn parameter_subtype(vl: Option<&Arc<dyn Feature>>, vr: Option<&Arc<dyn Feature>>, top: Option<&Arc<dyn Feature>>) -> bool {
+    match (vl, vr) {
+        (_, None) => true,
+        (Some(vlt), Some(vrt)) => vlt.feature_obj().subtypeof(&vrt.feature_obj()),
+        (None, Some(vrt)) => match top {
+            Some(top) => top.feature_obj().subtypeof(&vrt.feature_obj()),
+            None => unconstrained_subtype(vrt.as_ref()),
+        },
+    }
+}
+
+
+// whether an assumption value holds for a feature set that does not declare a parameter without top,
+// the missing value standing for the roots of the hierarchy
+fn satisfied_when_missing(f: &dyn Feature) -> bool {
+    match f.feature_obj() {
+        FeatureObj::Negation(excluded) => !satisfied_when_missing(excluded.as_ref()),
+        FeatureObj::Disjunction(alternatives) => alternatives.iter().any(|a| satisfied_when_missing(a.as_ref())),
+        FeatureObj::Conjunction(constraints) => constraints.iter().all(|c| satisfied_when_missing(c.as_ref())),
+        _ => f.supertype().is_none(),
+    }
+}
+
+// whether a clause that does not constrain a parameter without top is at least as specific as a value
+fn unconstrained_subtype(f: &dyn Feature) -> bool {
+    match f.feature_obj() {
+        // an unconstrained clause admits the excluded feature
+        FeatureObj::Negation(_) => false,
+        FeatureObj::Disjunction(alternatives) => alternatives.iter().any(|a| unconstrained_subtype(a.as_ref())),
+        FeatureObj::Conjunction(constraints) => constraints.iter().all(|c| unconstrained_subtype(c.as_ref())),
+        _ => f.supertype().is_none(),
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::AtLeast;
+    use crate::testing::{TestAvx, TestFpga, TestGpu, ampere, avx, cores, fpga, gpu, node, with_policy};
+    use super::*;
+
+    #[test]
+    fn the_most_specific_compatible_assumption_wins() {
+        let list = vec![Assumption::any_platform(), gpu(), ampere(), fpga()];
+        assert_eq!(most_specific(&node(), &list).unwrap(), 2);
+        assert_eq!(most_specific(&FeatureSet::new().with("test_acc", TestFpga), &list).unwrap(), 3);
+        assert_eq!(most_specific(&FeatureSet::new(), &list).unwrap(), 0);
+        assert_eq!(most_specific(&node(), &[fpga()]).unwrap(), -1);
+    }
+
+    #[test]
+    fn incomparable_assumptions_are_ambiguous() {
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        let ambiguity = most_specific(&node(), &list).unwrap_err();
+        assert_eq!(ambiguity.candidates, vec![1, 2]);
+        assert!(ambiguity.to_string().contains("#1") && ambiguity.to_string().contains("#2"));
+
+        // unless a compatible assumption is more specific than both
+        let both = Assumption::new(vec![FeatureSet::new().with("test_acc", TestGpu).with("test_simd", TestAvx)]);
+        assert_eq!(most_specific(&node(), &[list, vec![both]].concat()).unwrap(), 3);
+
+        // or only one is compatible
+        assert_eq!(most_specific(&FeatureSet::new().with("test_acc", TestGpu), &[Assumption::any_platform(), gpu(), avx()]).unwrap(), 1);
+    }
+
+    #[test]
+    fn equivalent_assumptions_tie_unless_priorities_differ() {
+        let list = vec![Assumption::any_platform(), gpu(), gpu()];
+        assert_eq!(most_specific(&node(), &list).unwrap_err().candidates, vec![1, 2]);
+
+        let list = vec![Assumption::any_platform(), gpu().with_priority(1), gpu()];
+        assert_eq!(most_specific(&node(), &list).unwrap(), 1);
+    }
+
+    #[test]
+    fn policies_choose_among_ties() {
+        assert_eq!(AmbiguityPolicy::FirstDeclared.choose(&[1, 3]), Some(1));
+        assert_eq!(AmbiguityPolicy::LastDeclared.choose(&[1, 3]), Some(3));
+        assert_eq!(AmbiguityPolicy::Fallback.choose(&[1, 3]), None);
+
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        let settled = |policy| with_policy(policy, || settle(most_specific(&node(), &list).unwrap_err()));
+        assert_eq!(settled(AmbiguityPolicy::FirstDeclared), 1);
+        assert_eq!(settled(AmbiguityPolicy::LastDeclared), 2);
+        assert_eq!(settled(AmbiguityPolicy::Fallback), -1);
+    }
+
+    #[test]
+    fn fallible_resolution_reports_the_ambiguity() {
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        with_platform(node(), || {
+            assert_eq!(try_resolve_assumptions(&list).unwrap_err().candidates, vec![1, 2]);
+            assert_eq!(try_resolve_assumptions(&list[..2]).unwrap(), 1);
+            with_policy(AmbiguityPolicy::Fallback, || assert_eq!(resolve_assumptions(list.clone()), -1));
+        });
+    }
+
+    #[test]
+    fn disjunctive_assumptions_are_compatible_through_any_clause() {
+        let either = Assumption::new(vec![
+            FeatureSet::new().with("test_acc", TestFpga),
+            FeatureSet::new().with("test_cores", AtLeast { val: 8 }),
+        ]);
+        assert!(is_compatible(&node(), &either));
+        assert!(!is_compatible(&FeatureSet::new().with("test_cores", 4), &either));
+        assert!(is_more_specific(&fpga(), &either));
+        assert!(!is_more_specific(&either, &fpga()));
+        assert!(is_more_specific(&cores(16), &either));
+    }
+
+    // the selection among the assumptions compatible with a platform
+    fn selected(platform: &FeatureSet, list: &[Assumption]) -> Result<i32, Vec<usize>> {
+        let compatible: Vec<usize> = (0..list.len()).filter(|&i| is_compatible(platform, &list[i])).collect();
+        select(list, &compatible)
+    }
+
+    #[test]
+    fn priority_ranks_incomparable_assumptions() {
+        let list = vec![Assumption::any_platform(), gpu(), avx().with_priority(5)];
+        assert_eq!(selected(&node(), &list), Ok(2));
+        let list = vec![Assumption::any_platform(), gpu().with_priority(5), avx(), cores(8).with_priority(-1)];
+        assert_eq!(selected(&node(), &list), Ok(1));
+
+        // among the highest priority only
+        let list = vec![Assumption::any_platform(), gpu().with_priority(5), avx().with_priority(5), cores(8)];
+        assert_eq!(selected(&node(), &list), Err(vec![1, 2]));
+    }
+
+    #[test]
+    fn priority_never_overrides_specificity() {
+        let list = vec![Assumption::any_platform().with_priority(100), gpu().with_priority(10), ampere()];
+        assert_eq!(selected(&node(), &list), Ok(2));
+        assert_eq!(ampere().with_priority(3).to_string(), format!("{} priority 3", ampere()));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{Assumption, FeatureSet};
+
+use super::{Ambiguity, ambiguity_policy, check_assumptions, compatible, current_platform, dominating, select, settle};
+
+/// A compatible assumption and its specificity relations with the other compatible ones.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RankedCandidate {
+    pub index: usize,
+    pub priority: i32,
+    /// compatible assumptions strictly more specific than this one
+    pub dominated_by: Vec<usize>,
+    /// compatible assumptions strictly less specific than this one
+    pub dominates: Vec<usize>,
+}
+
+/// The compatible assumptions of a list, see `resolve_all`.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resolution {
+    /// most preferred first
+    pub ranked: Vec<RankedCandidate>,
+    /// the candidates tied for the first place, when the ambiguity policy had to choose
+    pub ambiguous: Vec<usize>,
+}
+
+impl Resolution {
+
+    /// The first ranked index, what `resolve_assumptions` selects with the default resolver;
+    /// `None` when it selects the fallback (`-1`): no candidate is compatible, or the first
+    /// place is tied under `AmbiguityPolicy::Fallback`.
+    pub fn selected(&self) -> Option<usize> { self.ranked.first().map(|c| c.index) }
+
+    pub fn indices(&self) -> Vec<usize> { self.ranked.iter().map(|c| c.index).collect() }
+
+    pub fn candidate(&self, index: usize) -> Option<&RankedCandidate> { self.ranked.iter().find(|c| c.index == index) }
+}
+
+/// All the assumptions compatible with the current platform (see `current_platform`), in
+/// order of preference: each is the one selected once the previous ones are removed, i.e.
+/// by specificity, incomparable ones by priority and then by the ambiguity policy. Under
+/// `AmbiguityPolicy::Fallback`, the ranking stops at the first tie, as the fallback is
+/// selected there. A tie for the first place is reported as `resolve` reports it.
+pub fn resolve_all(assumption_list: &[Assumption]) -> Resolution {
+    check_assumptions(assumption_list);
+    resolve_all_on(&current_platform(), assumption_list)
+}
+
+/// `resolve_all` against a given platform.
+pub fn resolve_all_on(platform: &FeatureSet, assumption_list: &[Assumption]) -> Resolution {
+    let compatible = compatible(platform, assumption_list);
+    let (order, ambiguous) = preference_order(assumption_list, &compatible);
+
+    let ranked = order.into_iter()
+        .map(|index| RankedCandidate {
+            index,
+            priority: assumption_list[index].priority,
+            dominated_by: dominating(assumption_list, &compatible, index),
+            dominates: compatible.iter().copied()
+                .filter(|&j| dominating(assumption_list, &compatible, j).contains(&index))
+                .collect(),
+        })
+        .collect();
+
+    Resolution { ranked, ambiguous }
+}
+
+// the compatible candidates in order of preference and the candidates tied for the first
+// place. The first place is settled as `resolve` settles it (see `settle`, with a warning),
+// later ties by the ambiguity policy without a warning; under `AmbiguityPolicy::Fallback`,
+// the order stops at the first tie, where the fallback is selected.
+pub(super) fn preference_order(assumption_list: &[Assumption], compatible: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let policy = ambiguity_policy();
+    let mut remaining = compatible.to_vec();
+    let mut order = Vec::new();
+    let mut ambiguous = Vec::new();
+    while !remaining.is_empty() {
+        let next = match select(assumption_list, &remaining) {
+            Ok(i) => usize::try_from(i).ok(),
+            Err(ties) if order.is_empty() => {
+                ambiguous = ties.clone();
+                usize::try_from(settle(Ambiguity { candidates: ties, assumptions: assumption_list.to_vec() })).ok()
+            }
+            Err(ties) => policy.choose(&ties),
+        };
+        let Some(next) = next else { break };
+        remaining.retain(|&i| i != next);
+        order.push(next);
+    }
+    (order, ambiguous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ampere, avx, fpga, gpu, node, with_policy};
+    use crate::{AmbiguityPolicy, MostSpecific, Resolver, resolve_assumptions, with_platform};
+
+    #[test]
+    fn compatible_assumptions_are_ranked_by_specificity() {
+        let list = vec![Assumption::any_platform(), gpu(), ampere(), fpga()];
+        let resolution = resolve_all_on(&node(), &list);
+        assert_eq!(resolution.indices(), vec![2, 1, 0]);
+        assert_eq!(resolution.selected(), Some(2));
+        assert!(resolution.ambiguous.is_empty() && resolution.candidate(3).is_none());
+
+        let gpu = resolution.candidate(1).unwrap();
+        assert_eq!((gpu.dominated_by.clone(), gpu.dominates.clone()), (vec![2], vec![0]));
+        assert_eq!(resolution.candidate(0).unwrap().dominated_by, vec![1, 2]);
+        assert_eq!(resolution.candidate(2).unwrap().dominates, vec![0, 1]);
+
+        assert_eq!(resolve_all_on(&node(), &[fpga()]).selected(), None);
+    }
+
+    #[test]
+    fn ties_follow_priorities_then_the_policy() {
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        with_policy(AmbiguityPolicy::LastDeclared, || {
+            let resolution = resolve_all_on(&node(), &list);
+            assert_eq!((resolution.indices(), resolution.ambiguous), (vec![2, 1, 0], vec![1, 2]));
+        });
+        with_policy(AmbiguityPolicy::Fallback, || {
+            let resolution = resolve_all_on(&node(), &list);
+            assert_eq!((resolution.indices(), resolution.ambiguous), (vec![], vec![1, 2]));
+            let resolution = resolve_all_on(&node(), &[Assumption::any_platform(), gpu(), ampere(), avx().with_priority(-1)]);
+            assert_eq!(resolution.indices(), vec![2, 1, 3, 0]);
+        });
+
+        let ranked = vec![Assumption::any_platform(), gpu(), avx().with_priority(1)];
+        let resolution = resolve_all_on(&node(), &ranked);
+        assert_eq!(resolution.candidate(2).unwrap().priority, 1);
+        assert_eq!((resolution.indices(), resolution.ambiguous), (vec![2, 1, 0], vec![]));
+    }
+
+    #[test]
+    fn the_default_ranking_is_the_order_of_resolve_all() {
+        for policy in [AmbiguityPolicy::FirstDeclared, AmbiguityPolicy::LastDeclared, AmbiguityPolicy::Fallback] {
+            let list = vec![Assumption::any_platform(), gpu(), avx(), ampere(), fpga()];
+            with_policy(policy, || {
+                assert_eq!(MostSpecific.rank(&node(), &list), resolve_all_on(&node(), &list).indices());
+                assert_eq!(with_platform(node(), || resolve_all(&list)).indices(), resolve_all_on(&node(), &list).indices());
+            });
+        }
+    }
+
+    #[test]
+    fn resolve_selects_the_first_candidate_of_resolve_all() {
+        let lists = [
+            vec![Assumption::any_platform(), gpu(), ampere(), fpga()],
+            vec![Assumption::any_platform(), gpu(), avx()],
+            vec![gpu(), avx().with_priority(1)],
+            vec![fpga()],
+        ];
+        for policy in [AmbiguityPolicy::FirstDeclared, AmbiguityPolicy::LastDeclared, AmbiguityPolicy::Fallback] {
+            with_policy(policy, || for list in &lists {
+                let selected = with_platform(node(), || resolve_all(list).selected().map_or(-1, |i| i as i32));
+                assert_eq!(with_platform(node(), || resolve_assumptions(list.clone())), selected, "{list:?} under {policy:?}");
+            });
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+type Reporter = Arc<dyn Fn(&str) + Send + Sync>;
+
+static REPORTER: Lazy<Mutex<Reporter>> = Lazy::new(|| Mutex::new(Arc::new(|message: &str| eprintln!("{message}"))));
+
+// messages already reported
+static REPORTED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Installs the function receiving the warnings and errors of resolution (ambiguities,
+/// ill-formed assumptions, unknown feature names, failed variants), instead of printing
+/// them to the standard error.
+pub fn set_resolution_reporter<F: Fn(&str) + Send + Sync + 'static>(reporter: F) {
+    *REPORTER.lock().unwrap() = Arc::new(reporter);
+}
+
+/// Reports a warning or an error of resolution (`"warning: ..."`, `"error: ..."`) to the
+/// installed reporter, once per distinct message: dispatchers resolving on each call inside
+/// `with_platform` scopes would repeat them.
+pub fn report_resolution(message: String) {
+    if !REPORTED.lock().unwrap().insert(message.clone()) {
+        return;
+    }
+    let reporter = REPORTER.lock().unwrap().clone();
+    reporter(&message);
+}
+
+/// Reports that a variant of a dispatcher in `fallback_on_error` mode failed (see
+/// `#[configurable]`), as a warning to the installed reporter. Unlike `report_resolution`,
+/// every failure is reported: each is a distinct call.
+pub fn report_variant_failure(dispatcher: &str, variant: usize, next: usize) {
+    let reporter = REPORTER.lock().unwrap().clone();
+    reporter(&format!("warning: variant #{variant} of `{dispatcher}` failed, trying #{next}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{collect_reports, cores, gpu, node, with_policy};
+    use crate::{AmbiguityPolicy, Assumption, resolve_assumptions, with_platform};
+
+    #[test]
+    fn distinct_messages_are_reported_once() {
+        let reported = collect_reports("report_test_marker", || {
+            report_resolution("warning: report_test_marker one".to_string());
+            report_resolution("warning: report_test_marker two".to_string());
+            report_resolution("warning: report_test_marker one".to_string());
+        });
+        assert_eq!(reported, vec!["warning: report_test_marker one", "warning: report_test_marker two"]);
+    }
+
+    #[test]
+    fn every_failure_is_reported() {
+        let reported = collect_reports("report_test_kernel", || {
+            report_variant_failure("report_test_kernel", 2, 0);
+            report_variant_failure("report_test_kernel", 2, 0);
+        });
+        assert_eq!(reported, vec!["warning: variant #2 of `report_test_kernel` failed, trying #0"; 2]);
+    }
+
+    #[test]
+    fn scoped_resolutions_report_an_ambiguity_once() {
+        let list = vec![Assumption::any_platform(), gpu(), cores(13)];
+        let reported = collect_reports("atleast 13", || with_policy(AmbiguityPolicy::FirstDeclared, || {
+            with_platform(node(), || {
+                assert_eq!(resolve_assumptions(list.clone()), 1);
+                assert_eq!(resolve_assumptions(list.clone()), 1);
+            })
+        }));
+        assert_eq!(reported.len(), 1);
+        assert!(reported[0].starts_with("warning: ") && reported[0].ends_with("selecting #1 (FirstDeclared)"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use once_cell::sync::Lazy;
+
+use crate::{Assumption, FeatureSet};
+
+use super::{is_compatible, report_resolution, resolve_all_on, select, settle, Ambiguity};
+
+/// Strategy selecting the variant of a kernel function.
+///
+/// The default, `MostSpecific`, can be replaced globally with `set_resolver` or for the
+/// functions of a kernel module with `#[configurable(resolver = ...)]`. Closures
+/// `Fn(&FeatureSet, &[Assumption]) -> i32` are resolvers, ranking with the default `rank`.
+pub trait Resolver: Send + Sync {
+    /// Index of the selected assumption for the platform, `-1` if none is compatible
+    /// (the dispatcher then calls the fallback).
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32;
+
+    /// Indices of the assumptions in order of preference, the selected one first: each is
+    /// the one selected once the previous ones are removed.
+    fn rank(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> Vec<usize> {
+        let mut remaining: Vec<usize> = (0..assumptions.len()).collect();
+        let mut ranked = Vec::new();
+        loop {
+            let candidates: Vec<Assumption> = remaining.iter().map(|&i| assumptions[i].clone()).collect();
+            match usize::try_from(self.resolve(platform, &candidates)) {
+                Ok(k) if k < remaining.len() => ranked.push(remaining.remove(k)),
+                _ => return ranked,
+            }
+        }
+    }
+}
+
+impl<F: Fn(&FeatureSet, &[Assumption]) -> i32 + Send + Sync> Resolver for F {
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32 { self(platform, assumptions) }
+}
+
+/// The most specific compatible assumption, incomparable ones being ranked by priority and
+/// then settled by the ambiguity policy (see `try_resolve_assumptions`): the first candidate
+/// of `resolve_all_on`, and its order as ranking.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MostSpecific;
+
+impl Resolver for MostSpecific {
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32 {
+        resolve_all_on(platform, assumptions).selected().map_or(-1, |i| i as i32)
+    }
+
+    fn rank(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> Vec<usize> {
+        resolve_all_on(platform, assumptions).indices()
+    }
+}
+
+/// The first compatible assumption in declaration order, unconstrained ones (fallbacks)
+/// being considered only if no other is compatible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FirstCompatible;
+
+impl Resolver for FirstCompatible {
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32 {
+        let compatible = |i: &usize| is_compatible(platform, &assumptions[*i]);
+        (0..assumptions.len()).filter(compatible).find(|&i| !assumptions[i].is_unconstrained())
+            .or_else(|| (0..assumptions.len()).find(compatible))
+            .map_or(-1, |i| i as i32)
+    }
+}
+
+/// The compatible assumption of highest priority, whatever its specificity; assumptions
+/// sharing it are ranked like `MostSpecific`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PriorityOrdered;
+
+impl Resolver for PriorityOrdered {
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32 {
+        let top = assumptions.iter().filter(|a| is_compatible(platform, a)).map(|a| a.priority).max();
+        let ranked: Vec<usize> = (0..assumptions.len())
+            .filter(|&i| Some(assumptions[i].priority) == top && is_compatible(platform, &assumptions[i]))
+            .collect();
+        select(assumptions, &ranked)
+            .unwrap_or_else(|candidates| settle(Ambiguity { candidates, assumptions: assumptions.to_vec() }))
+    }
+}
+
+/// The compatible assumption of highest score, the first declared among equal scores.
+///
+/// # Example
+/// ```
+/// use configurable_features::{Scored, set_resolver};
+///
+/// // prefer the variants constraining the most parameters
+/// set_resolver(Scored::new(|_, a| a.clauses.iter().map(|c| c.len()).max().unwrap_or(0) as f64));
+/// ```
+pub struct Scored<F>(pub F);
+
+impl<F: Fn(&FeatureSet, &Assumption) -> f64 + Send + Sync> Scored<F> {
+    pub fn new(score: F) -> Self { Scored(score) }
+}
+
+impl<F: Fn(&FeatureSet, &Assumption) -> f64 + Send + Sync> Resolver for Scored<F> {
+    fn resolve(&self, platform: &FeatureSet, assumptions: &[Assumption]) -> i32 {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, a) in assumptions.iter().enumerate().filter(|(_, a)| is_compatible(platform, a)) {
+            let score = (self.0)(platform, a);
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((i, score));
+            }
+        }
+        best.map_or(-1, |(i, _)| i as i32)
+    }
+}
+
+pub static RESOLVER: Lazy<Mutex<Arc<dyn Resolver>>> = Lazy::new(|| Mutex::new(Arc::new(MostSpecific)));
+
+// whether `set_resolver` was called
+static RESOLVER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the resolver of the kernel modules that do not name one. Dispatchers resolve once,
+/// on their first call, so it must be installed before. Dispatchers pinned at compile time
+/// ignore it (see `check_pinned_resolver`).
+pub fn set_resolver<R: Resolver + 'static>(resolver: R) {
+    *RESOLVER.lock().unwrap() = Arc::new(resolver);
+    RESOLVER_INSTALLED.store(true, Ordering::Relaxed);
+}
+
+/// Called by dispatchers pinned at compile time (see `export_platform_pin`) on their first
+/// call: their variant was selected by the default resolver, so a resolver installed with
+/// `set_resolver` is reported as ignored (see `report_resolution`).
+pub fn check_pinned_resolver(dispatcher: &str) {
+    if RESOLVER_INSTALLED.load(Ordering::Relaxed) {
+        report_resolution(format!("warning: `{dispatcher}` is pinned at compile time, it ignores the resolver installed by `set_resolver`"));
+    }
+}
+
+pub fn resolver() -> Arc<dyn Resolver> { RESOLVER.lock().unwrap().clone() }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ampere, avx, collect_reports, fpga, gpu, node, with_policy, TestAvx, TestGpu};
+    use crate::AmbiguityPolicy;
+
+    #[test]
+    fn most_specific_resolves_by_specificity() {
+        let list = vec![Assumption::any_platform(), gpu(), ampere(), fpga()];
+        assert_eq!(MostSpecific.resolve(&node(), &list), 2);
+        assert_eq!(MostSpecific.resolve(&node(), &[fpga()]), -1);
+    }
+
+    #[test]
+    fn most_specific_settles_ties_by_the_policy() {
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        with_policy(AmbiguityPolicy::FirstDeclared, || assert_eq!(MostSpecific.resolve(&node(), &list), 1));
+        with_policy(AmbiguityPolicy::LastDeclared, || assert_eq!(MostSpecific.resolve(&node(), &list), 2));
+        with_policy(AmbiguityPolicy::Fallback, || assert_eq!(MostSpecific.resolve(&node(), &list), -1));
+    }
+
+    #[test]
+    fn most_specific_ranks_as_it_resolves() {
+        let list = vec![Assumption::any_platform(), gpu(), ampere(), fpga()];
+        assert_eq!(MostSpecific.rank(&node(), &list), vec![2, 1, 0]);
+        assert!(MostSpecific.rank(&node(), &[fpga()]).is_empty());
+
+        let list = vec![Assumption::any_platform(), gpu(), avx()];
+        with_policy(AmbiguityPolicy::FirstDeclared, || assert_eq!(MostSpecific.rank(&node(), &list), vec![1, 2, 0]));
+        with_policy(AmbiguityPolicy::LastDeclared, || assert_eq!(MostSpecific.rank(&node(), &list), vec![2, 1, 0]));
+        // the fallback is selected, and then tried alone
+        with_policy(AmbiguityPolicy::Fallback, || assert_eq!(MostSpecific.rank(&node(), &list), Vec::<usize>::new()));
+
+        // with the warning of `resolve`
+        let list = vec![Assumption::any_platform(), gpu().with_priority(7), avx().with_priority(7)];
+        let reports = with_policy(AmbiguityPolicy::LastDeclared, || collect_reports("priority 7", || { MostSpecific.rank(&node(), &list); }));
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].starts_with("warning:") && reports[0].contains("selecting #2"));
+    }
+
+    #[test]
+    fn the_default_ranking_resolves_repeatedly() {
+        let first = |platform: &FeatureSet, list: &[Assumption]| {
+            (0..list.len()).find(|&i| is_compatible(platform, &list[i])).map_or(-1, |i| i as i32)
+        };
+        let list = vec![Assumption::any_platform(), fpga(), gpu(), avx()];
+        assert_eq!(first.rank(&node(), &list), vec![0, 2, 3]);
+        assert_eq!(FirstCompatible.rank(&node(), &list), vec![2, 3, 0]);
+    }
+
+    #[test]
+    fn first_compatible_follows_declaration_order() {
+        let list = vec![Assumption::any_platform(), fpga(), gpu(), avx(), ampere()];
+        assert_eq!(FirstCompatible.resolve(&node(), &list), 2);
+        assert_eq!(FirstCompatible.rank(&node(), &list), vec![2, 3, 4, 0]);
+
+        // the fallback only when nothing else is compatible
+        assert_eq!(FirstCompatible.resolve(&node(), &[Assumption::any_platform(), fpga()]), 0);
+        assert_eq!(FirstCompatible.resolve(&node(), &[fpga()]), -1);
+    }
+
+    #[test]
+    fn priority_ordered_prefers_priority_over_specificity() {
+        let list = vec![Assumption::any_platform(), ampere(), gpu().with_priority(2)];
+        assert_eq!(PriorityOrdered.resolve(&node(), &list), 2);
+        assert_eq!(PriorityOrdered.rank(&node(), &list), vec![2, 1, 0]);
+        assert_eq!(MostSpecific.resolve(&node(), &list), 1);
+
+        // equal priorities rank by specificity
+        let list = vec![Assumption::any_platform(), gpu().with_priority(2), ampere().with_priority(2), fpga().with_priority(3)];
+        assert_eq!(PriorityOrdered.resolve(&node(), &list), 2);
+    }
+
+    #[test]
+    fn scored_selects_the_highest_score_first_declared_on_ties() {
+        let constrained = Scored::new(|_: &FeatureSet, a: &Assumption| a.clauses.iter().map(|c| c.len()).max().unwrap_or(0) as f64);
+        let both = Assumption::new(vec![FeatureSet::new().with("test_acc", TestGpu).with("test_simd", TestAvx)]);
+        let list = vec![Assumption::any_platform(), gpu(), both, fpga()];
+        assert_eq!(constrained.resolve(&node(), &list), 2);
+        assert_eq!(constrained.rank(&node(), &list), vec![2, 1, 0]);
+
+        let flat = Scored::new(|_: &FeatureSet, _: &Assumption| 1.0);
+        assert_eq!(flat.resolve(&node(), &[fpga(), gpu(), avx()]), 1);
+        assert_eq!(flat.resolve(&node(), &[fpga()]), -1);
+    }
+
+    #[test]
+    fn closures_are_resolvers() {
+        let last = |platform: &FeatureSet, list: &[Assumption]| {
+            (0..list.len()).rev().find(|&i| is_compatible(platform, &list[i])).map_or(-1, |i| i as i32)
+        };
+        let list = vec![Assumption::any_platform(), gpu(), fpga(), avx()];
+        assert_eq!(last.resolve(&node(), &list), 3);
+        assert_eq!(last.rank(&node(), &list), vec![3, 1, 0]);
+        let installed: Arc<dyn Resolver> = Arc::new(last);
+        assert_eq!(installed.resolve(&node(), &list), 3);
+    }
+
+    #[test]
+    fn pinned_dispatchers_report_an_installed_resolver() {
+        let reports = collect_reports("pinned_test::f", || check_pinned_resolver("pinned_test::f"));
+        assert!(reports.is_empty() || RESOLVER_INSTALLED.load(Ordering::Relaxed));
+
+        // the default resolver, as the other tests resolve with it
+        set_resolver(MostSpecific);
+        let reports = collect_reports("pinned_test::g", || check_pinned_resolver("pinned_test::g"));
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].contains("ignores the resolver installed by `set_resolver`"));
+    }
+}
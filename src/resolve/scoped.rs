@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+
+use crate::FeatureSet;
+
+thread_local! {
+    // innermost last
+    static PLATFORM_OVERRIDES: RefCell<Vec<FeatureSet>> = const { RefCell::new(Vec::new()) };
+}
+
+// pops the override when the scope ends, panicking or not
+struct OverrideGuard;
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        PLATFORM_OVERRIDES.with(|o| o.borrow_mut().pop());
+    }
+}
+
+/// Runs `f` with `platform` replacing `CURRENT_FEATURES` on the current thread (see
+/// `current_platform`). Dispatchers called meanwhile resolve against it instead of using
+/// their cached choice, except those resolved at compile time (see `export_platform_pin`).
+/// Scopes nest. Warnings of resolution are reported once (see `report_resolution`), however
+/// many calls repeat them.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use configurable_features::{AtLeast, Feature, FeatureSet, current_platform, insert_parameter, with_platform};
+///
+/// insert_parameter("cores".to_string(), Arc::new(AtLeast { val: 0 }));
+///
+/// let platform = FeatureSet::parse("cores = 16").unwrap();
+/// with_platform(platform, || assert_eq!(current_platform()["cores"].string(), "exactly 16"));
+/// assert!(!current_platform().contains_key("cores"));
+/// ```
+pub fn with_platform<P: Into<FeatureSet>, R>(platform: P, f: impl FnOnce() -> R) -> R {
+    PLATFORM_OVERRIDES.with(|o| o.borrow_mut().push(platform.into()));
+    let _guard = OverrideGuard;
+    f()
+}
+
+/// The platform of the innermost `with_platform` scope of the current thread.
+pub fn platform_override() -> Option<FeatureSet> {
+    PLATFORM_OVERRIDES.with(|o| o.borrow().last().cloned())
+}
+
+/// Whether the current thread runs in a `with_platform` scope, in which dispatchers resolve on
+/// each call instead of using their cached choice.
+pub fn is_platform_overridden() -> bool {
+    PLATFORM_OVERRIDES.with(|o| !o.borrow().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    use super::*;
+    use crate::{Feature, current_platform};
+    use crate::testing::{TestAmpere, TestFpga, node};
+
+    fn acc() -> Option<String> { current_platform().get("test_acc").map(|f| f.string()) }
+
+    #[test]
+    fn scopes_nest_and_end() {
+        assert!(!is_platform_overridden());
+        with_platform(node(), || {
+            assert!(is_platform_overridden());
+            assert_eq!(acc().as_deref(), Some(TestAmpere.string().as_str()));
+            with_platform(FeatureSet::new().with("test_acc", TestFpga), || {
+                assert_eq!(acc().as_deref(), Some(TestFpga.string().as_str()));
+            });
+            assert_eq!(platform_override().unwrap().len(), node().len());
+        });
+        assert!(!is_platform_overridden() && platform_override().is_none());
+    }
+
+    #[test]
+    fn scopes_end_on_panic() {
+        let result = catch_unwind(AssertUnwindSafe(|| with_platform(node(), || panic!("in scope"))));
+        assert!(result.is_err());
+        assert!(!is_platform_overridden());
+    }
+
+    #[test]
+    fn scopes_are_thread_local() {
+        with_platform(node(), || {
+            assert!(!std::thread::spawn(is_platform_overridden).join().unwrap());
+            assert!(is_platform_overridden());
+        });
+    }
+}
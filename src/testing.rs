@@ -0,0 +1,58 @@
+// Fixtures shared by the unit tests. The registries are global and tests run in parallel,
+// so tests that change a registry use parameter names of their own.
+
+use std::sync::{Arc, Mutex};
+
+use crate::{AmbiguityPolicy, Assumption, AtLeast, FeatureSet, create_feature_hierarchy, insert_parameter, set_ambiguity_policy, set_resolution_reporter};
+
+create_feature_hierarchy!{register_testing_acc_root ;"test_acc" : None :> TestAcc; }
+create_feature_hierarchy!{register_testing_acc_vendor ;"test_acc" : TestAcc :> TestGpu & TestFpga; }
+create_feature_hierarchy!{register_testing_acc_arch ;"test_acc" : TestGpu :> TestAmpere & TestHopper; }
+create_feature_hierarchy!{register_testing_acc_model ;"test_acc" : TestAmpere :> TestA100 & TestA30; }
+create_feature_hierarchy!{register_testing_simd_root ;"test_simd" : None :> TestSimd; }
+create_feature_hierarchy!{register_testing_simd ;"test_simd" : TestSimd :> /// Advanced Vector Extensions
+                                                                        TestAvx; }
+
+static POLICY: Mutex<()> = Mutex::new(());
+
+/// Runs `f` under an ambiguity policy; tests depending on the policy go through it.
+pub(crate) fn with_policy<R>(policy: AmbiguityPolicy, f: impl FnOnce() -> R) -> R {
+    let _lock = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    set_ambiguity_policy(policy);
+    let result = f();
+    set_ambiguity_policy(AmbiguityPolicy::default());
+    result
+}
+
+static COLLECTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The messages reported (see `report_resolution`) while running `f` that contain `marker`
+/// (other tests report concurrently).
+pub(crate) fn collect_reports(marker: &str, f: impl FnOnce()) -> Vec<String> {
+    static REPORTER_LOCK: Mutex<()> = Mutex::new(());
+    let _lock = REPORTER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    set_resolution_reporter(|message: &str| COLLECTED.lock().unwrap().push(message.to_string()));
+    f();
+    set_resolution_reporter(|message: &str| eprintln!("{message}"));
+    let mut collected = COLLECTED.lock().unwrap();
+    let (ours, _): (Vec<String>, Vec<String>) = collected.drain(..).partition(|m| m.contains(marker));
+    ours
+}
+
+pub(crate) fn declare_test_parameters() {
+    insert_parameter("test_acc".to_string(), Arc::new(TestAcc));
+    insert_parameter("test_simd".to_string(), Arc::new(TestSimd));
+    insert_parameter("test_cores".to_string(), Arc::new(AtLeast { val: 0 }));
+}
+
+pub(crate) fn gpu() -> Assumption { FeatureSet::new().with("test_acc", TestGpu).into() }
+pub(crate) fn ampere() -> Assumption { FeatureSet::new().with("test_acc", TestAmpere).into() }
+pub(crate) fn fpga() -> Assumption { FeatureSet::new().with("test_acc", TestFpga).into() }
+pub(crate) fn avx() -> Assumption { FeatureSet::new().with("test_simd", TestAvx).into() }
+pub(crate) fn cores(n: i32) -> Assumption { FeatureSet::new().with("test_cores", AtLeast { val: n }).into() }
+
+/// An Ampere node with AVX and 16 cores.
+pub(crate) fn node() -> FeatureSet {
+    declare_test_parameters();
+    FeatureSet::new().with("test_acc", TestAmpere).with("test_simd", TestAvx).with("test_cores", 16)
+}